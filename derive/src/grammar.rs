@@ -0,0 +1,567 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{bracketed, parenthesized, Attribute, Error, Ident, LitChar, LitStr, Path, Token, Type, Visibility};
+
+/// Options given to every rule of a grammar with `#![grammar(...)]`.
+struct Options {
+    krate: Path,
+    token: Type,
+    data: Type,
+    error: Type
+}
+
+pub struct Grammar {
+    options: Options,
+    rules: Vec<Rule>
+}
+
+struct Rule {
+    attributes: Vec<Attribute>,
+    visibility: Visibility,
+    name: Ident,
+    expression: Expression
+}
+
+enum Expression {
+    Choice(Vec<Expression>),
+    Sequence(Vec<Expression>),
+    Many(Box<Expression>),
+    Many1(Box<Expression>),
+    Optional(Box<Expression>),
+    Peek(Box<Expression>),
+    Not(Box<Expression>),
+    Literal(Option<Ident>, LitStr),
+    Class(Class),
+    Any,
+    Rule(Option<Ident>, Path)
+}
+
+struct Class {
+    negated: bool,
+    ranges: Vec<(LitChar, Option<LitChar>)>
+}
+
+/// How many times a label can be parsed, deciding the type of its field.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Cardinality {
+    One,
+    Optional,
+    Many
+}
+
+impl Parse for Grammar {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Options {
+            krate: syn::parse_quote!(::xfparser),
+            token: syn::parse_quote!(()),
+            data: syn::parse_quote!(()),
+            error: syn::parse_quote!(())
+        };
+
+        for attribute in input.call(Attribute::parse_inner)? {
+            if !attribute.path().is_ident("grammar") {
+                return Err(Error::new_spanned(attribute, "expected `#![grammar(...)]`"));
+            }
+
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    options.krate = meta.value()?.parse::<LitStr>()?.parse()?;
+                } else if meta.path.is_ident("token") {
+                    options.token = meta.value()?.parse()?;
+                } else if meta.path.is_ident("data") {
+                    options.data = meta.value()?.parse()?;
+                } else if meta.path.is_ident("error") {
+                    options.error = meta.value()?.parse()?;
+                } else {
+                    return Err(meta.error("unknown option, expected `crate`, `token`, `data` or `error`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let mut rules = Vec::new();
+        while !input.is_empty() {
+            rules.push(input.parse()?);
+        }
+
+        Ok(Self { options, rules })
+    }
+}
+
+impl Parse for Rule {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attributes = input.call(Attribute::parse_outer)?;
+        let visibility = input.parse()?;
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let expression = Expression::parse_choice(input)?;
+        input.parse::<Token![;]>()?;
+
+        Ok(Self { attributes, visibility, name, expression })
+    }
+}
+
+impl Expression {
+    fn parse_choice(input: ParseStream) -> syn::Result<Self> {
+        let mut alternatives = vec![Self::parse_sequence(input)?];
+        while input.peek(Token![/]) {
+            input.parse::<Token![/]>()?;
+            alternatives.push(Self::parse_sequence(input)?);
+        }
+
+        Ok(if alternatives.len() == 1 { alternatives.remove(0) } else { Self::Choice(alternatives) })
+    }
+
+    fn parse_sequence(input: ParseStream) -> syn::Result<Self> {
+        let mut items = Vec::new();
+        while !input.is_empty() && !input.peek(Token![/]) && !input.peek(Token![;]) {
+            items.push(Self::parse_prefixed(input)?);
+        }
+
+        match items.len() {
+            0 => Err(input.error("expected an expression")),
+            1 => Ok(items.remove(0)),
+            _ => Ok(Self::Sequence(items))
+        }
+    }
+
+    fn parse_prefixed(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![&]) {
+            input.parse::<Token![&]>()?;
+            Ok(Self::Peek(Box::new(Self::parse_suffixed(input)?)))
+        } else if input.peek(Token![!]) {
+            input.parse::<Token![!]>()?;
+            Ok(Self::Not(Box::new(Self::parse_suffixed(input)?)))
+        } else {
+            Self::parse_suffixed(input)
+        }
+    }
+
+    fn parse_suffixed(input: ParseStream) -> syn::Result<Self> {
+        let mut expression = Self::parse_primary(input)?;
+
+        loop {
+            expression = if input.peek(Token![*]) {
+                input.parse::<Token![*]>()?;
+                Self::Many(Box::new(expression))
+            } else if input.peek(Token![+]) {
+                input.parse::<Token![+]>()?;
+                Self::Many1(Box::new(expression))
+            } else if input.peek(Token![?]) {
+                input.parse::<Token![?]>()?;
+                Self::Optional(Box::new(expression))
+            } else {
+                return Ok(expression);
+            };
+        }
+    }
+
+    fn parse_primary(input: ParseStream) -> syn::Result<Self> {
+        let label = if input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]) {
+            let label = input.parse::<Ident>()?;
+            input.parse::<Token![:]>()?;
+            Some(label)
+        } else {
+            None
+        };
+
+        if input.peek(LitStr) {
+            return Ok(Self::Literal(label, input.parse()?));
+        }
+
+        if let Some(label) = label {
+            return match input.parse::<Path>() {
+                Ok(path) => Ok(Self::Rule(Some(label), path)),
+                Err(_) => Err(Error::new(label.span(), "only rules and literals can be labeled"))
+            };
+        }
+
+        if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            let expression = Self::parse_choice(&content)?;
+            if !content.is_empty() { return Err(content.error("expected `)`")) }
+            Ok(expression)
+        } else if input.peek(syn::token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            Ok(Self::Class(content.parse()?))
+        } else if input.peek(Token![.]) {
+            input.parse::<Token![.]>()?;
+            Ok(Self::Any)
+        } else {
+            Ok(Self::Rule(None, input.parse()?))
+        }
+    }
+}
+
+impl Parse for Class {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let negated = input.peek(Token![^]);
+        if negated { input.parse::<Token![^]>()?; }
+
+        let mut ranges = Vec::new();
+        while !input.is_empty() {
+            let start = input.parse()?;
+            let end = if input.peek(Token![-]) {
+                input.parse::<Token![-]>()?;
+                Some(input.parse()?)
+            } else {
+                None
+            };
+            ranges.push((start, end));
+        }
+
+        if ranges.is_empty() { return Err(input.error("expected a character")) }
+        Ok(Self { negated, ranges })
+    }
+}
+
+impl Class {
+    /// How the class is described when a character does not match it.
+    fn describe(&self) -> String {
+        let ranges = self.ranges
+            .iter()
+            .map(|(start, end)| match end {
+                Some(end) => format!("{:?}-{:?}", start.value(), end.value()),
+                None => format!("{:?}", start.value())
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!("[{}{ranges}]", if self.negated { "^" } else { "" })
+    }
+}
+
+/// Label of a rule, becoming a field of the rule's struct.
+struct Label {
+    name: Ident,
+    cardinality: Cardinality,
+    ty: TokenStream2
+}
+
+impl Label {
+    /// Local variable holding what the label parsed while the rule is being parsed.
+    fn local(&self) -> Ident {
+        format_ident!("label_{}", self.name)
+    }
+
+    fn field_type(&self) -> TokenStream2 {
+        let ty = &self.ty;
+        match self.cardinality {
+            Cardinality::One => quote!(#ty),
+            Cardinality::Optional => quote!(::core::option::Option<#ty>),
+            Cardinality::Many => quote!(::std::vec::Vec<#ty>)
+        }
+    }
+}
+
+impl Expression {
+    /// Find every label and how many times it can be parsed.
+    fn labels(&self, cardinality: Cardinality, options: &Options, labels: &mut Vec<Label>) -> syn::Result<()> {
+        let Options { krate, token, .. } = options;
+
+        match self {
+            Self::Choice(alternatives) => {
+                for alternative in alternatives {
+                    alternative.labels(cardinality.max(Cardinality::Optional), options, labels)?;
+                }
+            },
+            Self::Sequence(items) => {
+                for item in items {
+                    item.labels(cardinality, options, labels)?;
+                }
+            },
+            Self::Many(expression) | Self::Many1(expression) => expression.labels(Cardinality::Many, options, labels)?,
+            Self::Optional(expression) | Self::Peek(expression) => expression.labels(cardinality.max(Cardinality::Optional), options, labels)?,
+            Self::Not(expression) => {
+                if let Some(label) = expression.label_names().first() {
+                    return Err(Error::new(label.span(), "labels cannot be used in a negative lookahead"));
+                }
+            },
+            Self::Literal(Some(label), _) => add_label(labels, label, cardinality, quote!(#krate::ParserString<#token>))?,
+            Self::Rule(Some(label), path) => add_label(labels, label, cardinality, quote!(#krate::Node<#path>))?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn label_names(&self) -> Vec<&Ident> {
+        match self {
+            Self::Choice(expressions) | Self::Sequence(expressions) => expressions.iter().flat_map(Self::label_names).collect(),
+            Self::Many(expression) | Self::Many1(expression) | Self::Optional(expression) | Self::Peek(expression) | Self::Not(expression) => expression.label_names(),
+            Self::Literal(label, _) | Self::Rule(label, _) => label.iter().collect(),
+            _ => Vec::new()
+        }
+    }
+}
+
+fn add_label(labels: &mut Vec<Label>, name: &Ident, cardinality: Cardinality, ty: TokenStream2) -> syn::Result<()> {
+    if labels.iter().any(|label| label.name == *name) {
+        return Err(Error::new(name.span(), "labels must be unique within a rule"));
+    }
+
+    labels.push(Label { name: name.clone(), cardinality, ty });
+    Ok(())
+}
+
+/// Generates the code parsing the expression of a rule.
+struct Generator<'a> {
+    options: &'a Options,
+    labels: &'a [Label]
+}
+
+impl Generator<'_> {
+    fn label(&self, name: &Ident) -> &Label {
+        self.labels
+            .iter()
+            .find(|label| label.name == *name)
+            .expect("every label was collected before generating")
+    }
+
+    /// Statement storing `value` into a label.
+    fn assign(&self, name: &Option<Ident>) -> TokenStream2 {
+        let Some(name) = name else { return quote!() };
+        let label = self.label(name);
+        let local = label.local();
+
+        match label.cardinality {
+            Cardinality::Many => quote!(#local.push(value);),
+            _ => quote!(#local = ::core::option::Option::Some(value);)
+        }
+    }
+
+    /// Statements saving and restoring the labels in an expression, so that what a failed attempt
+    /// parsed is forgotten after rewinding.
+    fn backtrack(&self, expression: &Expression) -> (TokenStream2, TokenStream2) {
+        let mut save = Vec::new();
+        let mut restore = Vec::new();
+
+        for name in expression.label_names() {
+            let label = self.label(name);
+            let local = label.local();
+
+            match label.cardinality {
+                Cardinality::Many => {
+                    let saved = format_ident!("saved_{}", name);
+                    save.push(quote!(let #saved = #local.len();));
+                    restore.push(quote!(#local.truncate(#saved);));
+                },
+                _ => restore.push(quote!(#local = ::core::option::Option::None;))
+            }
+        }
+
+        (quote!(#(#save)*), quote!(#(#restore)*))
+    }
+
+    /// Expression of type `Result<(), Error<_>>` parsing an expression.
+    fn expression(&self, expression: &Expression) -> TokenStream2 {
+        let Options { krate, error, .. } = self.options;
+        let result = quote!(::core::result::Result<(), #krate::error::Error<#error>>);
+
+        match expression {
+            Expression::Sequence(items) => {
+                let items = items.iter().map(|item| self.expression(item));
+                quote!((|| -> #result {
+                    #((#items)?;)*
+                    ::core::result::Result::Ok(())
+                })())
+            },
+            Expression::Choice(alternatives) => {
+                let alternatives = alternatives.iter().map(|alternative| {
+                    let (save, restore) = self.backtrack(alternative);
+                    let parse = self.expression(alternative);
+
+                    quote! {
+                        #save
+                        match #parse {
+                            ::core::result::Result::Ok(()) => return ::core::result::Result::Ok(()),
+                            ::core::result::Result::Err(#krate::error::Error::SyntaxError(error)) => {
+                                failure = ::core::option::Option::Some(match failure.take() {
+                                    ::core::option::Option::Some(failure) => failure.furthest(error),
+                                    ::core::option::Option::None => error
+                                });
+                            },
+                            ::core::result::Result::Err(error) => return ::core::result::Result::Err(error)
+                        }
+                        parser.rewind(::core::clone::Clone::clone(&checkpoint));
+                        #restore
+                    }
+                });
+
+                quote!((|| -> #result {
+                    let checkpoint = parser.checkpoint();
+                    let mut failure = ::core::option::Option::<#krate::error::SyntaxError<#error>>::None;
+                    #(#alternatives)*
+
+                    match failure {
+                        ::core::option::Option::Some(failure) => ::core::result::Result::Err(#krate::error::Error::SyntaxError(failure)),
+                        ::core::option::Option::None => ::core::result::Result::Err(parser.unexpected())
+                    }
+                })())
+            },
+            Expression::Optional(expression) => {
+                let (save, restore) = self.backtrack(expression);
+                let parse = self.expression(expression);
+
+                quote!((|| -> #result {
+                    let checkpoint = parser.checkpoint();
+                    #save
+                    match #parse {
+                        ::core::result::Result::Err(#krate::error::Error::SyntaxError(_)) => {
+                            parser.rewind(checkpoint);
+                            #restore
+                            ::core::result::Result::Ok(())
+                        },
+                        result => result
+                    }
+                })())
+            },
+            Expression::Many(item) | Expression::Many1(item) => {
+                let (save, restore) = self.backtrack(item);
+                let parse = self.expression(item);
+                let minimum = usize::from(matches!(expression, Expression::Many1(_)));
+
+                quote!(#krate::grammar::repeat(parser, #minimum, |parser| -> #result {
+                    #save
+                    let result = #parse;
+                    if result.is_err() { #restore }
+                    result
+                }))
+            },
+            Expression::Peek(expression) => {
+                let parse = self.expression(expression);
+
+                quote!((|| -> #result {
+                    let checkpoint = parser.checkpoint();
+                    let result = #parse;
+                    parser.rewind(checkpoint);
+                    result
+                })())
+            },
+            Expression::Not(expression) => {
+                let parse = self.expression(expression);
+                quote!(#krate::grammar::not(parser, |parser| -> #result { #parse }))
+            },
+            Expression::Literal(label, literal) => {
+                let assign = self.assign(label);
+
+                quote! {
+                    match parser.expect_str(#literal) {
+                        ::core::result::Result::Ok(value) => {
+                            #assign
+                            ::core::result::Result::<(), #krate::error::Error<#error>>::Ok(())
+                        },
+                        ::core::result::Result::Err(_) => ::core::result::Result::Err(parser.expected([#krate::error::Expected::String(#literal.into())]))
+                    }
+                }
+            },
+            Expression::Rule(label, path) => {
+                let assign = self.assign(label);
+
+                quote! {
+                    match parser.parse::<#path>(data) {
+                        ::core::result::Result::Ok(value) => {
+                            #assign
+                            ::core::result::Result::<(), #krate::error::Error<#error>>::Ok(())
+                        },
+                        ::core::result::Result::Err(error) => ::core::result::Result::Err(error.map(::core::convert::From::from))
+                    }
+                }
+            },
+            Expression::Class(class) => {
+                let patterns = class.ranges.iter().map(|(start, end)| match end {
+                    Some(end) => quote!(#start..=#end),
+                    None => quote!(#start)
+                });
+                let negation = if class.negated { quote!(!) } else { quote!() };
+                let description = class.describe();
+
+                quote! {
+                    match parser.peek() {
+                        ::core::option::Option::Some(char) if #negation ::core::matches!(char, #(#patterns)|*) => {
+                            let _ = parser.expect_char(char);
+                            ::core::result::Result::<(), #krate::error::Error<#error>>::Ok(())
+                        },
+                        _ => ::core::result::Result::Err(parser.expected([#krate::error::Expected::Token(#description.into())]))
+                    }
+                }
+            },
+            Expression::Any => quote! {
+                match parser.peek() {
+                    ::core::option::Option::Some(char) => {
+                        let _ = parser.expect_char(char);
+                        ::core::result::Result::<(), #krate::error::Error<#error>>::Ok(())
+                    },
+                    ::core::option::Option::None => ::core::result::Result::Err(parser.expected([#krate::error::Expected::Token("any character".into())]))
+                }
+            }
+        }
+    }
+}
+
+impl Grammar {
+    pub fn expand(&self) -> syn::Result<TokenStream2> {
+        let rules = self.rules
+            .iter()
+            .map(|rule| self.expand_rule(rule))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(quote!(#(#rules)*))
+    }
+
+    fn expand_rule(&self, rule: &Rule) -> syn::Result<TokenStream2> {
+        let Options { krate, token, data, error } = &self.options;
+        let Rule { attributes, visibility, name, expression } = rule;
+
+        let mut labels = Vec::new();
+        expression.labels(Cardinality::One, &self.options, &mut labels)?;
+
+        let generator = Generator { options: &self.options, labels: &labels };
+        let parse = generator.expression(expression);
+
+        let fields = labels.iter().map(|label| {
+            let name = &label.name;
+            let ty = label.field_type();
+            quote!(#visibility #name: #ty)
+        });
+        let locals = labels.iter().map(|label| {
+            let local = label.local();
+            let ty = &label.ty;
+            match label.cardinality {
+                Cardinality::Many => quote!(let mut #local = ::std::vec::Vec::<#ty>::new();),
+                _ => quote!(let mut #local = ::core::option::Option::<#ty>::None;)
+            }
+        });
+        let constructs = labels.iter().map(|label| {
+            let name = &label.name;
+            let local = label.local();
+            match label.cardinality {
+                Cardinality::One => quote!(#name: #local.expect("label is parsed whenever the rule is")),
+                _ => quote!(#name: #local)
+            }
+        });
+
+        Ok(quote! {
+            #(#attributes)*
+            #visibility struct #name {
+                #(#fields),*
+            }
+
+            impl #krate::Parsable for #name {
+                type Error = #error;
+                type Token = #token;
+                type Data = #data;
+
+                #[allow(unused_variables, unused_mut, clippy::redundant_closure_call, clippy::result_large_err)]
+                fn parse(parser: &mut #krate::Parser<Self::Token>, data: &mut Self::Data) -> ::core::result::Result<Self, #krate::error::Error<Self::Error>> {
+                    #(#locals)*
+                    (#parse)?;
+                    ::core::result::Result::Ok(Self { #(#constructs),* })
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,333 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+mod grammar;
+
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, GenericArgument, LitStr, Path, PathArguments, Type};
+
+/// Options given to the type deriving `Parsable`.
+struct Container {
+    krate: Path,
+    token: Type,
+    data: Type,
+    error: Type,
+    /// Whether whitespace is skipped before every field.
+    space: bool
+}
+
+/// Options given to a field or a unit variant.
+#[derive(Default)]
+struct Field {
+    literal: Option<LitStr>,
+    with: Option<Path>,
+    map_err: Option<Path>,
+    space: bool
+}
+
+/// Derive `Parsable` for a struct or an enum.
+///
+/// # Usage
+/// Fields of a struct are parsed in order. Fields must either be a `Node` of another `Parsable`
+/// type or have an attribute saying how to parse them. Variants of an enum are tried in order,
+/// rewinding the parser between them, and their fields are parsed the same way as a struct's.
+///
+/// Options for the type, all of which are optional:
+/// - `#[parsable(token = Type)]`, `#[parsable(data = Type)]` and `#[parsable(error = Type)]` set
+///   the associated types, defaulting to `()`.
+/// - `#[parsable(space)]` skips whitespace before every field.
+/// - `#[parsable(crate = "path")]` sets the path of the parser crate.
+///
+/// Options for a field or a unit variant:
+/// - `#[parsable(literal = "text")]` expects the text, storing it as a `ParserString`.
+/// - `#[parsable(space)]` skips whitespace before the field.
+/// - `#[parsable(with = path)]` parses the field with a function taking the parser and data.
+/// - `#[parsable(map_err = path)]` converts the specific error of the field with a function.
+///   Without it, errors are converted with `From`.
+#[proc_macro_derive(Parsable, attributes(parsable))]
+pub fn derive_parsable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Define the rules of a grammar inline, each becoming a struct that implements `Parsable`.
+///
+/// # Usage
+/// Rules are written as `Name = expression;`, optionally preceded by attributes and a
+/// visibility which are given to the struct. Expressions are parsing expressions:
+/// - `"text"` expects the text and `.` expects any character.
+/// - `['a'-'z' '_']` expects a character in the class, and `[^ '"']` one that is not.
+/// - `Name` parses another rule, or any other `Parsable` type with the same token and data.
+/// - `a b` parses in sequence and `a / b` tries each alternative in order, rewinding between
+///   them.
+/// - `a*`, `a+` and `a?` repeat or make an expression optional, and `(a)` groups.
+/// - `&a` and `!a` look ahead without consuming anything.
+///
+/// Labeling a rule or text with `label: Name` stores what it parsed in a field of the rule,
+/// which is a `Node` or `ParserString`, an `Option` of one if it is optional or in an
+/// alternative, and a `Vec` of them if it is repeated. Everything else a rule parses is only
+/// available through the span of its node.
+///
+/// Options for every rule are given with `#![grammar(...)]` at the start of the grammar, taking
+/// `token`, `data`, `error` and `crate` the same way as `#[parsable(...)]`.
+///
+/// ```ignore
+/// grammar! {
+///     #![grammar(token = Token)]
+///
+///     #[derive(Debug)]
+///     pub Number = ['0'-'9']+;
+///     #[derive(Debug)]
+///     pub Operator = "+" / "-";
+///     #[derive(Debug)]
+///     pub Sum = first: Number (operators: Operator rest: Number)*;
+/// }
+/// ```
+#[proc_macro]
+pub fn grammar(input: TokenStream) -> TokenStream {
+    let grammar = parse_macro_input!(input as grammar::Grammar);
+    grammar.expand().unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let container = Container::parse(&input.attrs)?;
+    let Container { krate, token, data, error, .. } = &container;
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let construct = construct(&container, quote!(Self), &data.fields, None)?;
+            quote!(::core::result::Result::Ok(#construct))
+        },
+        Data::Enum(data) => {
+            let attempts = data.variants
+                .iter()
+                .map(|variant| {
+                    let name = &variant.ident;
+                    let field = Field::parse(&variant.attrs)?;
+                    let construct = construct(&container, quote!(Self::#name), &variant.fields, Some(field))?;
+
+                    Ok(quote! {
+                        let attempt = (|| -> ::core::result::Result<Self, #krate::error::Error<Self::Error>> {
+                            ::core::result::Result::Ok(#construct)
+                        })();
+
+                        match attempt {
+                            ::core::result::Result::Ok(node) => return ::core::result::Result::Ok(node),
+                            ::core::result::Result::Err(#krate::error::Error::SyntaxError(error)) => {
+                                failure = ::core::option::Option::Some(match failure.take() {
+                                    ::core::option::Option::Some(failure) => failure.furthest(error),
+                                    ::core::option::Option::None => error
+                                });
+                            },
+                            ::core::result::Result::Err(error) => return ::core::result::Result::Err(error)
+                        }
+                        parser.rewind(::core::clone::Clone::clone(&checkpoint));
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            quote! {
+                let checkpoint = parser.checkpoint();
+                let mut failure = ::core::option::Option::<#krate::error::SyntaxError<Self::Error>>::None;
+                #(#attempts)*
+
+                match failure {
+                    ::core::option::Option::Some(failure) => ::core::result::Result::Err(#krate::error::Error::SyntaxError(failure)),
+                    ::core::option::Option::None => ::core::result::Result::Err(parser.unexpected())
+                }
+            }
+        },
+        Data::Union(data) => return Err(Error::new_spanned(data.union_token, "Parsable cannot be derived for unions"))
+    };
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #krate::Parsable for #name #type_generics #where_clause {
+            type Error = #error;
+            type Token = #token;
+            type Data = #data;
+
+            #[allow(unused_variables, clippy::redundant_closure_call, clippy::result_large_err)]
+            fn parse(parser: &mut #krate::Parser<Self::Token>, data: &mut Self::Data) -> ::core::result::Result<Self, #krate::error::Error<Self::Error>> {
+                #body
+            }
+        }
+    })
+}
+
+/// Expression parsing every field in order and constructing the type or variant from them.
+///
+/// Unit types and variants are only constructed after parsing the literal they are given.
+fn construct(container: &Container, path: TokenStream2, fields: &Fields, unit: Option<Field>) -> syn::Result<TokenStream2> {
+    if let Fields::Unit = fields {
+        let unit = unit.unwrap_or_default();
+        let space = skip_space(container, &unit);
+        let literal = match &unit.literal {
+            Some(literal) => {
+                let parse = expect_literal(container, literal);
+                quote!(#parse;)
+            },
+            None => quote!()
+        };
+
+        return Ok(quote!({
+            #space
+            #literal
+            #path
+        }));
+    }
+
+    let mut statements = Vec::new();
+    let mut bindings = Vec::new();
+
+    for (index, field) in fields.iter().enumerate() {
+        let options = Field::parse(&field.attrs)?;
+        let binding = format_ident!("field_{index}");
+        let space = skip_space(container, &options);
+        let parse = parse_field(container, &options, &field.ty)?;
+
+        statements.push(quote! {
+            #space
+            let #binding = #parse;
+        });
+        bindings.push(match &field.ident {
+            Some(name) => quote!(#name: #binding),
+            None => quote!(#binding)
+        });
+    }
+
+    let construct = match fields {
+        Fields::Named(_) => quote!(#path { #(#bindings),* }),
+        _ => quote!(#path(#(#bindings),*))
+    };
+
+    Ok(quote!({
+        #(#statements)*
+        #construct
+    }))
+}
+
+fn skip_space(container: &Container, field: &Field) -> TokenStream2 {
+    let Container { krate, token, .. } = container;
+
+    if container.space || field.space {
+        // Whitespace is optional, so only errors that stop parsing are returned.
+        quote! {
+            if let Err(error) = parser.parse::<#krate::node::space::Whitespace<#token>>(&mut ()) {
+                if let Ok(fatal) = error.fatal() { return Err(fatal) }
+            }
+        }
+    } else {
+        quote!()
+    }
+}
+
+fn expect_literal(container: &Container, literal: &LitStr) -> TokenStream2 {
+    let Container { krate, error, .. } = container;
+
+    quote! {
+        parser
+            .expect_str(#literal)
+            .map_err(|_| -> #krate::error::Error<#error> {
+                parser.expected([#krate::error::Expected::String(#literal.into())])
+            })?
+    }
+}
+
+/// Expression parsing a single field.
+fn parse_field(container: &Container, field: &Field, ty: &Type) -> syn::Result<TokenStream2> {
+    let Container { krate, error, .. } = container;
+
+    if let Some(literal) = &field.literal {
+        return Ok(expect_literal(container, literal));
+    }
+
+    let parse = match &field.with {
+        Some(with) => quote!(#with(parser, data)),
+        None => {
+            let node = node_type(ty).ok_or_else(|| Error::new_spanned(ty, "field must be a Node or have a `literal` or `with` attribute"))?;
+            quote!(parser.parse::<#node>(data))
+        }
+    };
+
+    let map = match &field.map_err {
+        Some(map_err) => quote!(#map_err),
+        None => quote!(::core::convert::From::from)
+    };
+
+    Ok(quote! {
+        #parse.map_err(|error| -> #krate::error::Error<#error> { error.map(#map) })?
+    })
+}
+
+/// The type parsed by a field of type `Node<Type>`.
+fn node_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Node" { return None }
+
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else { return None };
+    match arguments.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None
+    }
+}
+
+impl Container {
+    fn parse(attributes: &[Attribute]) -> syn::Result<Self> {
+        let mut container = Self {
+            krate: syn::parse_quote!(::xfparser),
+            token: syn::parse_quote!(()),
+            data: syn::parse_quote!(()),
+            error: syn::parse_quote!(()),
+            space: false
+        };
+
+        for attribute in attributes.iter().filter(|attribute| attribute.path().is_ident("parsable")) {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    container.krate = meta.value()?.parse::<LitStr>()?.parse()?;
+                } else if meta.path.is_ident("token") {
+                    container.token = meta.value()?.parse()?;
+                } else if meta.path.is_ident("data") {
+                    container.data = meta.value()?.parse()?;
+                } else if meta.path.is_ident("error") {
+                    container.error = meta.value()?.parse()?;
+                } else if meta.path.is_ident("space") {
+                    container.space = true;
+                } else {
+                    return Err(meta.error("unknown option, expected `crate`, `token`, `data`, `error` or `space`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(container)
+    }
+}
+
+impl Field {
+    fn parse(attributes: &[Attribute]) -> syn::Result<Self> {
+        let mut field = Self::default();
+
+        for attribute in attributes.iter().filter(|attribute| attribute.path().is_ident("parsable")) {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("literal") {
+                    field.literal = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("with") {
+                    field.with = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("map_err") {
+                    field.map_err = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("space") {
+                    field.space = true;
+                } else {
+                    return Err(meta.error("unknown option, expected `literal`, `with`, `map_err` or `space`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(field)
+    }
+}
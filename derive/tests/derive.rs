@@ -0,0 +1,135 @@
+#![allow(clippy::result_large_err)]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use xfparser::{Limits, Node, Parsable, Parser, ParserString};
+use xfparser::error::{Error, Expected};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Keyword
+}
+
+#[derive(Debug)]
+struct Name;
+
+impl Parsable for Name {
+    type Error = ();
+    type Token = Token;
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, _: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let name = parser.parse_while(char::is_alphabetic);
+        if name.is_empty() { return Err(parser.expected([Expected::Node("name".into())])) }
+        Ok(Self)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct NumberError;
+
+impl From<()> for NumberError {
+    fn from(_: ()) -> Self {
+        Self
+    }
+}
+
+fn number(parser: &mut Parser<Token>, _: &mut ()) -> Result<u32, Error<()>> {
+    let digits = parser.parse_while(|character| character.is_ascii_digit());
+    digits.parse().map_err(|_| parser.expected([Expected::Node("number".into())]))
+}
+
+#[derive(Debug, Parsable)]
+#[parsable(token = Token, error = NumberError, space)]
+struct Assignment {
+    #[parsable(literal = "let")]
+    keyword: ParserString<Token>,
+    name: Node<Name>,
+    #[parsable(literal = "=")]
+    equals: ParserString<Token>,
+    #[parsable(with = number)]
+    value: u32
+}
+
+#[derive(Debug, Parsable)]
+#[parsable(token = Token, space)]
+#[allow(dead_code)]
+struct Keyword {
+    #[parsable(literal = "let")]
+    keyword: ParserString<Token>
+}
+
+#[derive(Debug, Parsable)]
+#[parsable(token = Token)]
+#[allow(dead_code)]
+enum Statement {
+    Assignment(#[parsable(map_err = drop)] Node<Assignment>),
+    #[parsable(literal = "pass")]
+    Pass,
+    Block {
+        #[parsable(literal = "{")]
+        open: ParserString<Token>,
+        #[parsable(space)]
+        #[parsable(literal = "}")]
+        close: ParserString<Token>
+    }
+}
+
+#[test]
+fn derive_struct() {
+    let mut parser = Parser::<Token>::new("let answer = 42");
+    parser.internalize("let", Token::Keyword).unwrap();
+
+    let node = parser.parse::<Assignment>(&mut ()).unwrap();
+    assert_eq!(node.keyword.token().as_deref(), Some(&Token::Keyword));
+    assert_eq!(node.name.slice(), "answer");
+    assert_eq!(&*node.equals, "=");
+    assert_eq!(node.value, 42);
+}
+
+#[test]
+fn derive_struct_error() {
+    let mut parser = Parser::<Token>::new("let answer = x");
+    let error = parser.parse::<Assignment>(&mut ()).unwrap_err();
+
+    let error = error.syntax().unwrap();
+    assert_eq!(error.span().byte_start, 13);
+    assert!(error.expected().contains(&Expected::Node("number".into())));
+}
+
+#[test]
+fn derive_space_stops() {
+    let limits = Limits { depth: None, steps: Some(1) };
+    let mut parser = Parser::<Token>::new(" let").with_limits(limits);
+    assert!(matches!(parser.parse::<Keyword>(&mut ()), Err(Error::LimitExceeded(_))));
+
+    let checks = Arc::new(AtomicUsize::new(0));
+    let mut parser = Parser::<Token>::new(" let")
+        .with_cancellation_check(move || checks.fetch_add(1, Ordering::Relaxed) > 0);
+    assert!(matches!(parser.parse::<Keyword>(&mut ()), Err(Error::Cancelled(_))));
+}
+
+#[test]
+fn derive_enum() {
+    let mut parser = Parser::<Token>::new("pass");
+    assert!(matches!(*parser.parse::<Statement>(&mut ()).unwrap(), Statement::Pass));
+
+    let mut parser = Parser::<Token>::new("{ }");
+    assert!(matches!(*parser.parse::<Statement>(&mut ()).unwrap(), Statement::Block { .. }));
+
+    let mut parser = Parser::<Token>::new("let a = 1");
+    assert!(matches!(*parser.parse::<Statement>(&mut ()).unwrap(), Statement::Assignment(_)));
+}
+
+#[test]
+fn derive_enum_error() {
+    let mut parser = Parser::<Token>::new("fail");
+    let error = parser.parse::<Statement>(&mut ()).unwrap_err();
+
+    let error = error.syntax().unwrap();
+    assert_eq!(error.span().byte_start, 0);
+    assert!(error.expected().contains(&Expected::String("let".into())));
+    assert!(error.expected().contains(&Expected::String("pass".into())));
+    assert!(error.expected().contains(&Expected::String("{".into())));
+}
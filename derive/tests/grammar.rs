@@ -0,0 +1,66 @@
+#![allow(clippy::result_large_err)]
+
+use xfparser::{grammar, Parser};
+use xfparser::error::Expected;
+
+grammar! {
+    #[derive(Debug)]
+    Number = ['0'-'9']+;
+    #[derive(Debug)]
+    Operator = "+" / "-";
+    #[derive(Debug)]
+    Space = [' ' '\t']*;
+    #[derive(Debug)]
+    Sum = first: Number (Space operators: Operator Space rest: Number)* !.;
+
+    #[derive(Debug)]
+    Word = ['a'-'z' 'A'-'Z' '_' '-']+;
+    #[derive(Debug)]
+    Url = protocol: Word "://" host: Word ("." domains: Word)* path: Path?;
+    #[derive(Debug)]
+    Path = "/" [^ ' ']*;
+}
+
+#[test]
+fn sequence_and_repetition() {
+    let mut parser = Parser::new("1 + 23 - 4");
+    let sum = parser.parse::<Sum>(&mut ()).unwrap();
+
+    assert_eq!(sum.first.slice(), "1");
+    assert_eq!(sum.operators.iter().map(|operator| operator.slice()).collect::<Vec<_>>(), ["+", "-"]);
+    assert_eq!(sum.rest.iter().map(|number| number.slice()).collect::<Vec<_>>(), ["23", "4"]);
+}
+
+#[test]
+fn backtracking_forgets_labels() {
+    let mut parser = Parser::new("rust.rs.");
+    let url = parser.parse::<Url>(&mut ());
+    assert!(url.is_err());
+
+    let mut parser = Parser::new("https://rust.rs/learn");
+    let url = parser.parse::<Url>(&mut ()).unwrap();
+    assert_eq!(url.protocol.slice(), "https");
+    assert_eq!(url.domains.len(), 1);
+    assert_eq!(url.path.as_ref().map(|path| path.slice()), Some("/learn"));
+
+    let mut parser = Parser::new("ws://host");
+    let url = parser.parse::<Url>(&mut ()).unwrap();
+    assert!(url.domains.is_empty() && url.path.is_none());
+}
+
+#[test]
+fn errors() {
+    let mut parser = Parser::new("*");
+    let error = parser.parse::<Operator>(&mut ()).unwrap_err();
+    let error = error.syntax().unwrap();
+    assert_eq!(error.span().byte_start, 0);
+    assert!(error.expected().contains(&Expected::String("+".into())));
+    assert!(error.expected().contains(&Expected::String("-".into())));
+
+    let mut parser = Parser::new("x");
+    let error = parser.parse::<Number>(&mut ()).unwrap_err();
+    assert!(error.syntax().unwrap().expected().contains(&Expected::Token("['0'-'9']".into())));
+
+    let mut parser = Parser::new("1 2");
+    assert!(parser.parse::<Sum>(&mut ()).is_err());
+}
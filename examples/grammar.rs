@@ -0,0 +1,26 @@
+use xfparser::{grammar, Parser};
+
+grammar! {
+    #[derive(Debug)]
+    Number = ['0'-'9']+;
+    #[derive(Debug)]
+    Operator = "+" / "-";
+    Whitespace = [' ' '\t' '\n']*;
+    #[derive(Debug)]
+    Sum = Whitespace first: Number (Whitespace operators: Operator Whitespace rest: Number)* Whitespace;
+
+    #[derive(Debug)]
+    Word = ['a'-'z' 'A'-'Z' '_' '-']+;
+    #[derive(Debug)]
+    Url = protocol: Word "://" host: Word ("." domains: Word)*;
+}
+
+fn main() {
+    let mut parser = Parser::<()>::new("1 + 23 - 4");
+    let sum = parser.parse::<Sum>(&mut ()).unwrap();
+    dbg!(sum.first.slice(), sum.rest.len());
+
+    let mut parser = Parser::<()>::new("https://rust.rs");
+    let url = parser.parse::<Url>(&mut ()).unwrap();
+    dbg!(url.protocol.slice(), url.host.slice());
+}
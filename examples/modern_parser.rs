@@ -1,4 +1,5 @@
-use parser::error::Error;
+use parser::diagnostic::{Diagnostic, Renderer};
+use parser::error::{Error, Expected};
 use parser::{Parsable, Parser};
 use thiserror::Error;
 
@@ -40,10 +41,10 @@ impl Parsable for Keyword {
         }))).unwrap();
 
         if let Some(token) = token {
-            let Token::Keyword(keyword_token) = *token;
+            let Token::Keyword(keyword_token) = *parser.interner().token(token).expect("the keyword was just internalized");
             Ok(Self { word: keyword_token })
         } else {
-            Err(Error::new_syntax_temp())
+            Err(parser.expected([Expected::Token("keyword".into())]))
         }
     }
 }
@@ -52,6 +53,12 @@ fn main() {
     let source = "struct";
     let mut parser = Parser::new(source);
 
-    let token = parser.parse::<Keyword>(&mut ()).expect("Failed to parse keyword token");
-    dbg!(token.word);
+    match parser.parse::<Keyword>(&mut ()) {
+        Ok(token) => { dbg!(token.word); },
+        Err(Error::SyntaxError(error)) => {
+            let source_map = parser.source_map();
+            eprint!("{}", Renderer::new(&source_map).with_file_name("<input>").render(&Diagnostic::from(&error)));
+        },
+        Err(error) => panic!("{error}")
+    }
 }
\ No newline at end of file
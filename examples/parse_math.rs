@@ -62,7 +62,7 @@ impl Parsable for Operator {
             _ => return None
         }))).unwrap();
         if let Some(tk) = token {
-            println!("got token {:?}", &*tk);
+            println!("got token {:?}", parser.interner().token(tk));
         }
         
         Ok(Self { operator: None })
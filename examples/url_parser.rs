@@ -1,5 +1,5 @@
 use parser::{Parsable, Parser, ParserString};
-use parser::error::Error;
+use parser::error::{Error, Expected};
 
 #[derive(Debug, Clone)]
 enum Token {
@@ -18,14 +18,8 @@ impl Parsable for Protocol {
     type Data = ();
 
     fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
-        let mut symbols = parser.parse_while(|character| matches!(character, ':' | '/'));
-        let token = symbols.try_internalize(|_| None).unwrap();
-        
-        if let None = token {
-            Err(Error::new_syntax_temp())
-        } else {
-           Ok(Self) 
-        }
+        parser.expect_str("://").map_err(|_| parser.expected([Expected::String("://".into())]))?;
+        Ok(Self)
     }
 }
 
@@ -44,7 +38,7 @@ impl Parsable for Word {
         let mut word = parser.parse_while(|character| character.is_alphabetic() || matches!(character, '_' | '-'));
         word.try_internalize(|_| Some(Token::Identifier));
         if word.len() == 0 {
-            return Err(Error::new_syntax_temp());
+            return Err(parser.expected([Expected::Node("word".into())]));
         }
         
         Ok(Self {
@@ -73,4 +67,9 @@ fn main() {
     dbg!(&*start.word.token().unwrap());
     let proto = parser.parse::<Protocol>(&mut ()).unwrap();
     dbg!(proto.slice());
+    
+    // the host is not parsed yet, so it is left over
+    if let Err(Error::SyntaxError(error)) = parser.expect_eof::<()>() {
+        println!("{:?} at {:?}: {}", error.kind(), error.span().byte_range(), error.position_message());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,14 @@
+pub mod choice;
+pub mod repeat;
+pub mod optional;
+pub mod lookahead;
+pub mod recover;
+
+#[cfg(test)]
+mod test;
+
+pub use choice::Either as Either;
+pub use repeat::{Many, Many1, SepBy, SepByTrailing};
+pub use optional::Optional as Optional;
+pub use lookahead::{Peek, Not};
+pub use recover::{Recover, Synchronize};
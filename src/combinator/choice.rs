@@ -0,0 +1,64 @@
+use crate::{Node, Parsable, Parser};
+use crate::error::{Error, ErrorKind, SyntaxError};
+
+/// Ordered choice between two nodes.
+/// 
+/// # Usage
+/// The left node is tried first and the right node is only tried if the left node fails to parse, 
+/// after rewinding the parser to where the left node started. Nest another [Either] as the right 
+/// node to choose between more than two nodes.
+#[derive(Debug, Clone)]
+pub enum Either<Left, Right> {
+    Left(Node<Left>),
+    Right(Node<Right>)
+}
+
+/// The errors of every alternative that was tried by [Either], in the order they were tried.
+/// 
+/// # Usage
+/// The syntax error containing this takes the position of whichever alternative failed furthest 
+/// into the source, expecting everything the alternatives that failed there expected.
+#[derive(Debug, PartialEq)]
+pub struct Alternatives<Left, Right> {
+    pub left: Error<Left>,
+    pub right: Error<Right>
+}
+
+impl<Left, Right> Parsable for Either<Left, Right>
+where
+    Left: Parsable + 'static,
+    Right: Parsable<Token = Left::Token, Data = Left::Data> + 'static
+{
+    type Error = Alternatives<Left::Error, Right::Error>;
+    type Token = Left::Token;
+    type Data = Left::Data;
+
+    fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let checkpoint = parser.checkpoint();
+        
+        let left = match parser.parse::<Left>(data) {
+            Ok(node) => return Ok(Self::Left(node)),
+            Err(error) => match error.fatal() {
+                Ok(fatal) => return Err(fatal),
+                Err(error) => error
+            }
+        };
+        parser.rewind(checkpoint.clone());
+        
+        let right = match parser.parse::<Right>(data) {
+            Ok(node) => return Ok(Self::Right(node)),
+            Err(error) => match error.fatal() {
+                Ok(fatal) => return Err(fatal),
+                Err(error) => error
+            }
+        };
+        parser.rewind(checkpoint);
+        
+        let span = parser.span().at_end().map_err(Error::ArithmeticOverflow)?;
+        let mut error = SyntaxError::<()>::new(ErrorKind::Unexpected, span);
+        if let Some(left) = left.syntax() { error.merge_furthest(left) }
+        if let Some(right) = right.syntax() { error.merge_furthest(right) }
+        
+        Err(Error::SyntaxError(error.with_kind(ErrorKind::Specific(Alternatives { left, right }))))
+    }
+}
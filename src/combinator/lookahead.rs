@@ -0,0 +1,53 @@
+use core::marker::PhantomData;
+use crate::{Node, Parsable, Parser};
+use crate::error::{Error, ErrorKind, Found, SyntaxError};
+
+/// Positive lookahead of a node.
+/// 
+/// # Usage
+/// The node is parsed but nothing is consumed, so this node always has an empty span while the 
+/// item keeps the span it was parsed from.
+#[derive(Debug, Clone)]
+pub struct Peek<Item> {
+    pub item: Node<Item>
+}
+
+/// Negative lookahead of a node.
+/// 
+/// # Usage
+/// Succeeds without consuming anything only if the node fails to parse. If the node does parse, 
+/// the error spans what it would have consumed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Not<Item>(PhantomData<Item>);
+
+impl<Item: Parsable + 'static> Parsable for Peek<Item> {
+    type Error = Item::Error;
+    type Token = Item::Token;
+    type Data = Item::Data;
+
+    fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        // The derived parser has its own position, so parsing with it consumes nothing from this 
+        // parser.
+        let item = parser.derive().map_err(Error::ArithmeticOverflow)?.parse::<Item>(data)?;
+        Ok(Self { item })
+    }
+}
+
+impl<Item: Parsable + 'static> Parsable for Not<Item> {
+    type Error = Item::Error;
+    type Token = Item::Token;
+    type Data = Item::Data;
+
+    fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let checkpoint = parser.checkpoint();
+        
+        match parser.detach_failures().parse::<Item>(data) {
+            Ok(item) => Err(Error::SyntaxError(SyntaxError::new(ErrorKind::Unexpected, *item.span()).with_found(Found::String(item.source.clone())))),
+            Err(Error::SyntaxError(_)) => {
+                parser.rewind(checkpoint);
+                Ok(Self(PhantomData))
+            },
+            Err(error) => Err(error)
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{Node, Parsable, Parser};
+use crate::error::Error;
+
+/// A node that may be absent.
+/// 
+/// # Usage
+/// Parsing never fails. If the node fails to parse, nothing is consumed and the item is [None].
+#[derive(Debug, Clone)]
+pub struct Optional<Item> {
+    pub item: Option<Node<Item>>
+}
+
+impl<Item: Parsable + 'static> Parsable for Optional<Item> {
+    type Error = Item::Error;
+    type Token = Item::Token;
+    type Data = Item::Data;
+
+    fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let checkpoint = parser.checkpoint();
+        let item = match parser.parse::<Item>(data) {
+            Ok(item) => Some(item),
+            Err(Error::SyntaxError(_)) => {
+                parser.rewind(checkpoint);
+                None
+            },
+            Err(error) => return Err(error)
+        };
+        
+        Ok(Self { item })
+    }
+}
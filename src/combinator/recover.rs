@@ -0,0 +1,124 @@
+use core::marker::PhantomData;
+use crate::{Node, Parsable, Parser};
+use crate::diagnostic::Diagnostic;
+use crate::error::{Error, SyntaxError};
+
+/// What to do with a character while skipping source to recover from a syntax error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Synchronization {
+    /// Skip the character and keep looking for a synchronization point.
+    Skip,
+    /// Resume parsing at the character, such as a closing brace that belongs to an enclosing 
+    /// block.
+    Before,
+    /// Skip the character and resume parsing after it, such as a semicolon ending a statement.
+    After
+}
+
+/// A set of points where parsing can resume after a syntax error.
+/// 
+/// # Usage
+/// Implemented by marker types given to [Recover]. Tuples of sets synchronize at the first point 
+/// of any of their sets.
+pub trait Synchronize {
+    fn synchronize(character: char) -> Synchronization;
+}
+
+/// Synchronizes after a `;`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Semicolon;
+
+/// Synchronizes after a line break.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Newline;
+
+/// Synchronizes before a `}`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CloseBrace;
+
+impl Synchronize for Semicolon {
+    fn synchronize(character: char) -> Synchronization {
+        if character == ';' { Synchronization::After } else { Synchronization::Skip }
+    }
+}
+
+impl Synchronize for Newline {
+    fn synchronize(character: char) -> Synchronization {
+        if character == '\n' { Synchronization::After } else { Synchronization::Skip }
+    }
+}
+
+impl Synchronize for CloseBrace {
+    fn synchronize(character: char) -> Synchronization {
+        if character == '}' { Synchronization::Before } else { Synchronization::Skip }
+    }
+}
+
+impl<First: Synchronize, Second: Synchronize> Synchronize for (First, Second) {
+    fn synchronize(character: char) -> Synchronization {
+        match First::synchronize(character) {
+            Synchronization::Skip => Second::synchronize(character),
+            synchronization => synchronization
+        }
+    }
+}
+
+impl<First: Synchronize, Second: Synchronize, Third: Synchronize> Synchronize for (First, Second, Third) {
+    fn synchronize(character: char) -> Synchronization {
+        <(First, (Second, Third))>::synchronize(character)
+    }
+}
+
+/// A node that recovers from syntax errors by skipping to a synchronization point.
+/// 
+/// # Usage
+/// If the node fails with a syntax error, the error is reported to the parser as a diagnostic 
+/// and the source up to the synchronization point is skipped. The item is then [None] and this 
+/// node spans the skipped source, acting as a placeholder for the node that failed which keeps 
+/// the syntax error it failed with. Repeating 
+/// this node with [crate::combinator::Many] parses every node it can while reporting every 
+/// error.
+/// 
+/// # Error
+/// The syntax error is returned instead if no source could be skipped, such as at the end of 
+/// the source, so that repetition stops.
+#[derive(Debug, Clone)]
+pub struct Recover<Item: Parsable, Set> {
+    pub item: Option<Node<Item>>,
+    /// The syntax error the item failed with, if this node is a placeholder for it.
+    pub error: Option<SyntaxError<Item::Error>>,
+    synchronize: PhantomData<Set>
+}
+
+impl<Item: Parsable + 'static, Set: Synchronize + 'static> Parsable for Recover<Item, Set> {
+    type Error = Item::Error;
+    type Token = Item::Token;
+    type Data = Item::Data;
+
+    fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let error = match parser.parse::<Item>(data) {
+            Ok(item) => return Ok(Self { item: Some(item), error: None, synchronize: PhantomData }),
+            Err(Error::SyntaxError(error)) => error,
+            Err(error) => return Err(error)
+        };
+        
+        let mut synchronized = false;
+        let skipped = parser.parse_while(|character| {
+            if synchronized { return false }
+            
+            match Set::synchronize(character) {
+                Synchronization::Skip => true,
+                Synchronization::Before => false,
+                Synchronization::After => {
+                    synchronized = true;
+                    true
+                }
+            }
+        });
+        
+        if skipped.is_empty() { return Err(Error::SyntaxError(error)) }
+        parser.report(Diagnostic::from_position(&error));
+        
+        Ok(Self { item: None, error: Some(error), synchronize: PhantomData })
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{Node, Parsable, Parser};
+use crate::error::Error;
+
+/// Repetition of a node at least `MIN` and at most `MAX` times.
+/// 
+/// # Usage
+/// Items are parsed until one fails or `MAX` items were parsed. The node spans every item, while 
+/// each item keeps its own span. An item that consumes nothing ends the repetition once `MIN` 
+/// items were parsed, because it would be parsed again at the same position forever.
+#[derive(Debug, Clone)]
+pub struct Many<Item, const MIN: usize = 0, const MAX: usize = { usize::MAX }> {
+    pub items: Vec<Node<Item>>
+}
+
+/// Repetition of a node at least once.
+pub type Many1<Item> = Many<Item, 1>;
+
+/// Repetition of a node separated by another node, with no separator after the last item.
+/// 
+/// # Usage
+/// A separator is only consumed if an item follows it. See [Many] for how `MIN` and `MAX` are 
+/// used.
+#[derive(Debug, Clone)]
+pub struct SepBy<Item, Separator, const MIN: usize = 0, const MAX: usize = { usize::MAX }> {
+    pub items: Vec<Node<Item>>,
+    pub separators: Vec<Node<Separator>>
+}
+
+/// Same as [SepBy] but also consumes a separator after the last item if there is one.
+#[derive(Debug, Clone)]
+pub struct SepByTrailing<Item, Separator, const MIN: usize = 0, const MAX: usize = { usize::MAX }> {
+    pub items: Vec<Node<Item>>,
+    pub separators: Vec<Node<Separator>>
+}
+
+/// Error for when fewer than the minimum amount of items were parsed, using the error of the item 
+/// that failed if there is one.
+fn too_few<Token, SpecificError>(parser: &Parser<Token>, error: Option<Error<SpecificError>>) -> Error<SpecificError> {
+    error.unwrap_or_else(|| parser.unexpected())
+}
+
+impl<Item: Parsable + 'static, const MIN: usize, const MAX: usize> Parsable for Many<Item, MIN, MAX> {
+    type Error = Item::Error;
+    type Token = Item::Token;
+    type Data = Item::Data;
+
+    fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let mut items = Vec::new();
+        let mut error = None;
+        
+        while items.len() < MAX {
+            let checkpoint = parser.checkpoint();
+            match parser.parse::<Item>(data) {
+                Ok(item) => {
+                    let empty = item.span().byte_length == 0;
+                    items.push(item);
+                    if empty && items.len() >= MIN { break }
+                },
+                Err(item_error @ Error::SyntaxError(_)) => {
+                    parser.rewind(checkpoint);
+                    error = Some(item_error);
+                    break
+                },
+                Err(item_error) => return Err(item_error)
+            }
+        }
+        
+        if items.len() < MIN { return Err(too_few(parser, error)) }
+        Ok(Self { items })
+    }
+}
+
+/// Parse items separated by separators, also parsing a separator after the last item if 
+/// `trailing` is set.
+fn separated<Item, Separator>(parser: &mut Parser<Item::Token>, data: &mut Item::Data, min: usize, max: usize, trailing: bool) -> Result<SepBy<Item, Separator>, Error<Item::Error>>
+where
+    Item: Parsable + 'static,
+    Separator: Parsable<Token = Item::Token, Data = Item::Data> + 'static
+{
+    let mut items = Vec::new();
+    let mut separators = Vec::new();
+    let mut error = None;
+    
+    while items.len() < max {
+        let checkpoint = parser.checkpoint();
+        
+        let separator = if items.is_empty() {
+            None
+        } else {
+            match parser.parse::<Separator>(data).map_err(Error::fatal) {
+                Ok(separator) => Some(separator),
+                Err(Ok(fatal)) => return Err(fatal),
+                Err(Err(_)) => {
+                    parser.rewind(checkpoint);
+                    break
+                }
+            }
+        };
+        
+        match parser.parse::<Item>(data) {
+            Ok(item) => {
+                let empty = item.span().byte_length == 0 && separator.as_ref().is_none_or(|separator| separator.span().byte_length == 0);
+                separators.extend(separator);
+                items.push(item);
+                if empty && items.len() >= min { break }
+            },
+            Err(item_error @ Error::SyntaxError(_)) => {
+                match separator {
+                    Some(separator) if trailing => separators.push(separator),
+                    _ => parser.rewind(checkpoint)
+                }
+                
+                error = Some(item_error);
+                break
+            },
+            Err(item_error) => return Err(item_error)
+        }
+    }
+    
+    if items.len() < min { return Err(too_few(parser, error)) }
+    Ok(SepBy { items, separators })
+}
+
+impl<Item, Separator, const MIN: usize, const MAX: usize> Parsable for SepBy<Item, Separator, MIN, MAX>
+where
+    Item: Parsable + 'static,
+    Separator: Parsable<Token = Item::Token, Data = Item::Data> + 'static
+{
+    type Error = Item::Error;
+    type Token = Item::Token;
+    type Data = Item::Data;
+
+    fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let SepBy { items, separators } = separated::<Item, Separator>(parser, data, MIN, MAX, false)?;
+        Ok(Self { items, separators })
+    }
+}
+
+impl<Item, Separator, const MIN: usize, const MAX: usize> Parsable for SepByTrailing<Item, Separator, MIN, MAX>
+where
+    Item: Parsable + 'static,
+    Separator: Parsable<Token = Item::Token, Data = Item::Data> + 'static
+{
+    type Error = Item::Error;
+    type Token = Item::Token;
+    type Data = Item::Data;
+
+    fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let SepBy { items, separators } = separated::<Item, Separator>(parser, data, MIN, MAX, true)?;
+        Ok(Self { items, separators })
+    }
+}
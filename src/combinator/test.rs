@@ -0,0 +1,176 @@
+use crate::{Parsable, Parser};
+use crate::combinator::{Either, Many, Many1, Not, Optional, Peek, Recover, SepBy, SepByTrailing};
+use crate::combinator::recover::Semicolon;
+use crate::error::{Error, ErrorKind, Expected, Found};
+
+/// Parses a single character, failing on anything that is not `C`.
+#[derive(Debug)]
+struct Char<const C: char>;
+
+impl<const C: char> Parsable for Char<C> {
+    type Error = ();
+    type Token = ();
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, _: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        parser.expect_char(C).map_err(|_| parser.expected([Expected::Char(C)]))?;
+        Ok(Self)
+    }
+}
+
+/// Parses "ab" after internalizing the "a", so a failure leaves a string to be rolled back.
+#[derive(Debug)]
+struct AB;
+
+impl Parsable for AB {
+    type Error = ();
+    type Token = ();
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, _: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let mut a = parser.parse_while(|character| character == 'a');
+        a.try_internalize(|_| Some(())).unwrap();
+        parser.expect_char('b').map_err(|_| parser.expected([Expected::Char('b')]))?;
+        Ok(Self)
+    }
+}
+
+#[test]
+fn either() {
+    let mut parser = Parser::<()>::new("ac");
+    let node = parser.parse::<Either<AB, Either<Char<'b'>, Char<'a'>>>>(&mut ()).unwrap();
+
+    assert!(matches!(&*node, Either::Right(node) if matches!(&**node, Either::Right(_))));
+    assert_eq!(node.slice(), "a");
+    assert!(parser.internalize("a", ()).is_ok());
+}
+
+#[test]
+fn either_fails() {
+    let mut parser = Parser::<()>::new("c");
+    let error = parser.parse::<Either<Char<'a'>, Char<'b'>>>(&mut ()).unwrap_err();
+
+    let Error::SyntaxError(error) = error else { panic!("expected a syntax error") };
+    assert!(matches!(error.kind(), ErrorKind::Specific(_)));
+    assert_eq!(error.span().byte_range(), 0..1);
+    assert_eq!(error.found(), Some(&Found::Char('c')));
+    assert!(error.expected().iter().eq(&[Expected::Char('a'), Expected::Char('b')]));
+    assert_eq!(parser.position(), 0);
+}
+
+#[test]
+fn parse_first_of() {
+    let mut parser = Parser::<()>::new("b");
+    let node = parser.parse_first_of(&mut (), &[
+        |parser, data| parser.parse::<Char<'a'>>(data).map(|_| 'a'),
+        |parser, data| parser.parse::<Char<'b'>>(data).map(|_| 'b')
+    ]).unwrap();
+
+    assert_eq!(*node, 'b');
+    assert_eq!(parser.position(), 1);
+}
+
+#[test]
+fn many() {
+    let mut parser = Parser::<()>::new("aaab");
+    let node = parser.parse::<Many<Char<'a'>, 0, 2>>(&mut ()).unwrap();
+
+    assert_eq!(node.items.len(), 2);
+    assert_eq!(node.slice(), "aa");
+    assert_eq!(node.items[1].span().byte_start, 1);
+    assert!(parser.parse::<Many1<Char<'b'>>>(&mut ()).is_err());
+}
+
+#[test]
+fn sep_by() {
+    let mut parser = Parser::<()>::new("a,a,b");
+    let node = parser.parse::<SepBy<Char<'a'>, Char<','>>>(&mut ()).unwrap();
+
+    assert_eq!(node.items.len(), 2);
+    assert_eq!(node.separators.len(), 1);
+    assert_eq!(node.slice(), "a,a");
+    assert_eq!(parser.remaining(), ",b");
+}
+
+#[test]
+fn sep_by_trailing() {
+    let mut parser = Parser::<()>::new("a,a,b");
+    let node = parser.parse::<SepByTrailing<Char<'a'>, Char<','>, 3>>(&mut ());
+    assert!(node.is_err());
+    assert_eq!(parser.position(), 0);
+
+    let node = parser.parse::<SepByTrailing<Char<'a'>, Char<','>>>(&mut ()).unwrap();
+    assert_eq!(node.separators.len(), 2);
+    assert_eq!(parser.remaining(), "b");
+}
+
+#[test]
+fn optional() {
+    let mut parser = Parser::<()>::new("ab");
+
+    assert!(parser.parse::<Optional<Char<'b'>>>(&mut ()).unwrap().item.is_none());
+    assert_eq!(parser.position(), 0);
+    assert!(parser.parse::<Optional<Char<'a'>>>(&mut ()).unwrap().item.is_some());
+    assert_eq!(parser.position(), 1);
+}
+
+#[test]
+fn lookahead() {
+    let mut parser = Parser::<()>::new("ab");
+
+    let node = parser.parse::<Peek<Char<'a'>>>(&mut ()).unwrap();
+    assert_eq!(node.span().byte_length, 0);
+    assert_eq!(node.item.slice(), "a");
+    assert!(parser.parse::<Peek<Char<'b'>>>(&mut ()).is_err());
+
+    assert!(parser.parse::<Not<Char<'b'>>>(&mut ()).is_ok());
+    assert!(parser.parse::<Not<Char<'a'>>>(&mut ()).is_err());
+    assert_eq!(parser.position(), 0);
+}
+
+#[test]
+fn furthest_failure() {
+    let mut parser = Parser::<()>::new("ac");
+    let error = parser.parse::<Either<AB, Char<'b'>>>(&mut ()).unwrap_err();
+
+    let error = error.syntax().unwrap();
+    assert_eq!(error.span().byte_start, 1);
+    assert!(error.expected().iter().eq(&[Expected::Char('b')]));
+}
+
+/// Parses a statement of "a" or "b" ending with a ";".
+#[derive(Debug)]
+struct Statement;
+
+impl Parsable for Statement {
+    type Error = ();
+    type Token = ();
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        parser.parse::<Either<Char<'a'>, Char<'b'>>>(data).map_err(|error| error.map(|_| ()))?;
+        parser.parse::<Char<';'>>(data)?;
+        Ok(Self)
+    }
+}
+
+#[test]
+fn recover() {
+    let mut parser = Parser::<()>::new("a;cc;b;a");
+    let (node, diagnostics) = parser.parse_with_diagnostics::<Many<Recover<Statement, Semicolon>>>(&mut ());
+    let node = node.unwrap();
+
+    assert_eq!(node.items.len(), 4);
+    assert!(node.items[1].item.is_none());
+    assert_eq!(node.items[1].slice(), "cc;");
+    let error = node.items[1].error.as_ref().unwrap();
+    assert_eq!(error.found(), Some(&Found::Char('c')));
+    assert!(error.expected().iter().eq(&[Expected::Char('a'), Expected::Char('b')]));
+    assert!(node.items[0].error.is_none());
+    assert!(node.items[3].item.is_none());
+    assert_eq!(node.items[3].slice(), "a");
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].message, "unexpected 'c', expected 'a' or 'b'");
+    assert_eq!(diagnostics[1].message, "unexpected end of input, expected ';'");
+}
@@ -0,0 +1,283 @@
+use core::fmt::{self, Display, Write};
+use crate::error::SyntaxError;
+use crate::source_map::{Location, SourceMap};
+use crate::span::Span;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Help
+}
+
+/// A span of source that a diagnostic points at, optionally with a message shown under it.
+/// 
+/// # Usage
+/// Primary labels mark where the problem is and are underlined with carets, secondary labels 
+/// mark related source and are underlined with dashes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: Option<String>,
+    pub primary: bool
+}
+
+/// A message about the source to be shown to a user, such as a syntax error.
+/// 
+/// # Usage
+/// Built with the `with_` methods and turned into text with a [Renderer].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>
+}
+
+/// Renders diagnostics as text in the style of rustc, with the lines of source they point at.
+#[derive(Debug, Clone, Copy)]
+pub struct Renderer<'a> {
+    source_map: &'a SourceMap,
+    file_name: Option<&'a str>,
+    color: bool
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+            Self::Help => "help"
+        })
+    }
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new()
+        }
+    }
+    
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+    
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+    
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+    
+    pub fn with_primary(self, span: Span, message: Option<String>) -> Self {
+        self.with_label(Label { span, message, primary: true })
+    }
+    
+    pub fn with_secondary(self, span: Span, message: Option<String>) -> Self {
+        self.with_label(Label { span, message, primary: false })
+    }
+    
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+    
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+    
+    /// Create an error from where a syntax error occurred, for syntax errors whose specific error 
+    /// cannot be displayed.
+    pub fn from_position<SpecificError>(error: &SyntaxError<SpecificError>) -> Self {
+        Self::error(error.position_message()).with_primary(*error.span(), Self::found_label(error))
+    }
+    
+    fn found_label<SpecificError>(error: &SyntaxError<SpecificError>) -> Option<String> {
+        error.found().map(|found| format!("unexpected {found}"))
+    }
+}
+
+impl<SpecificError: Display> From<&SyntaxError<SpecificError>> for Diagnostic {
+    fn from(error: &SyntaxError<SpecificError>) -> Self {
+        Self::error(error.to_string()).with_primary(*error.span(), Self::found_label(error))
+    }
+}
+
+/// ANSI escape codes used when rendering with color.
+mod style {
+    pub const RESET: &str = "\x1b[0m";
+    pub const BOLD: &str = "\x1b[1m";
+    pub const RED: &str = "\x1b[1;31m";
+    pub const YELLOW: &str = "\x1b[1;33m";
+    pub const GREEN: &str = "\x1b[1;32m";
+    pub const CYAN: &str = "\x1b[1;36m";
+    pub const BLUE: &str = "\x1b[1;34m";
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(source_map: &'a SourceMap) -> Self {
+        Self {
+            source_map,
+            file_name: None,
+            color: false
+        }
+    }
+    
+    /// Set the file name shown before the line and column of a diagnostic.
+    pub fn with_file_name(mut self, file_name: &'a str) -> Self {
+        self.file_name = Some(file_name);
+        self
+    }
+    
+    /// Set whether ANSI escape codes are used to color the output.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+    
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut output = String::new();
+        // Writing into a string cannot fail.
+        let _ = self.write(&mut output, diagnostic);
+        output
+    }
+    
+    pub fn write(&self, output: &mut impl Write, diagnostic: &Diagnostic) -> fmt::Result {
+        let severity_style = Self::severity_style(diagnostic.severity);
+        writeln!(output, "{}{}", self.paint(severity_style, &diagnostic.severity.to_string()), self.paint(style::BOLD, &format!(": {}", diagnostic.message)))?;
+        
+        // Every line that a label touches, with the label and the columns it covers on that line.
+        let mut lines = Vec::<(usize, Vec<(&Label, usize, usize, bool)>)>::new();
+        for label in &diagnostic.labels {
+            let (start, end) = self.label_bounds(label);
+            
+            for line in start.line..=end.line {
+                let length = self.display_width(line);
+                let from = if line == start.line { start.column } else { 1 };
+                let to = if line == end.line { end.column } else { length + 1 };
+                
+                let index = lines.partition_point(|(other, _)| *other < line);
+                if lines.get(index).is_none_or(|(other, _)| *other != line) {
+                    lines.insert(index, (line, Vec::new()));
+                }
+                lines[index].1.push((label, from, to.max(from + 1), line == end.line));
+            }
+        }
+        
+        let gutter = lines.last().map_or(1, |(line, _)| line.to_string().len());
+        let bar = self.paint(style::BLUE, "|");
+        
+        let primary = diagnostic.labels.iter().find(|label| label.primary).or(diagnostic.labels.first());
+        if let Some(label) = primary {
+            let location = self.source_map.location(label.span.byte_start);
+            let file_name = self.file_name.map_or_else(String::new, |file_name| format!("{file_name}:"));
+            writeln!(output, "{:gutter$}{} {file_name}{}:{}", "", self.paint(style::BLUE, "-->"), location.line, location.column)?;
+        }
+        
+        if !lines.is_empty() {
+            writeln!(output, "{:gutter$} {bar}", "")?;
+        }
+        
+        let mut previous = None;
+        for (line, labels) in &lines {
+            if let Some(previous) = previous && line - previous > 1 {
+                writeln!(output, "{}", self.paint(style::BLUE, "..."))?;
+            }
+            previous = Some(*line);
+            
+            let content = self.expand_tabs(self.source_map.line(*line).unwrap_or_default());
+            writeln!(output, "{} {bar} {content}", self.paint(style::BLUE, &format!("{line:>gutter$}")))?;
+            
+            for (label, from, to, last) in labels {
+                let (marker, marker_style) = if label.primary { ('^', severity_style) } else { ('-', style::BLUE) };
+                let mut underline: String = core::iter::repeat_n(marker, to - from).collect();
+                if *last && let Some(message) = &label.message {
+                    underline.push(' ');
+                    underline.push_str(message);
+                }
+                
+                writeln!(output, "{:gutter$} {bar} {:padding$}{}", "", "", self.paint(marker_style, &underline), padding = from - 1)?;
+            }
+        }
+        
+        for note in &diagnostic.notes {
+            writeln!(output, "{:gutter$} {} {}: {note}", "", self.paint(style::BLUE, "="), self.paint(style::BOLD, "note"))?;
+        }
+        
+        for help in &diagnostic.help {
+            writeln!(output, "{:gutter$} {} {}: {help}", "", self.paint(style::BLUE, "="), self.paint(style::BOLD, "help"))?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Locations of the first and last character covered by a label.
+    fn label_bounds(&self, label: &Label) -> (Location, Location) {
+        let location = self.source_map.resolve(&label.span);
+        let mut end = location.end;
+        
+        // A span ending with a line break ends on the line before, not at the start of the next.
+        if end.line > location.start.line && end.column == 1 {
+            end.line -= 1;
+            end.column = self.display_width(end.line) + 1;
+        }
+        
+        (location.start, end)
+    }
+    
+    /// Width of a line in columns, with tabs expanded.
+    fn display_width(&self, line: usize) -> usize {
+        self.expand_tabs(self.source_map.line(line).unwrap_or_default()).chars().count()
+    }
+    
+    /// Replace tabs with spaces up to the next tab stop so columns line up with the underlines.
+    fn expand_tabs(&self, line: &str) -> String {
+        let tab_width = self.source_map.tab_width();
+        let mut expanded = String::with_capacity(line.len());
+        let mut column = 0;
+        
+        for character in line.chars() {
+            if character == '\t' {
+                let width = tab_width - column % tab_width;
+                expanded.extend(core::iter::repeat_n(' ', width));
+                column += width;
+            } else {
+                expanded.push(character);
+                column += 1;
+            }
+        }
+        
+        expanded
+    }
+    
+    fn severity_style(severity: Severity) -> &'static str {
+        match severity {
+            Severity::Error => style::RED,
+            Severity::Warning => style::YELLOW,
+            Severity::Note => style::GREEN,
+            Severity::Help => style::CYAN
+        }
+    }
+    
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{style}{text}{}", style::RESET)
+        } else {
+            text.to_owned()
+        }
+    }
+}
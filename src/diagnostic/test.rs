@@ -0,0 +1,67 @@
+use crate::diagnostic::{Diagnostic, Renderer};
+use crate::error::{ErrorKind, Expected, Found, SyntaxError};
+use crate::source_map::SourceMap;
+use crate::span::Span;
+
+fn span(byte_start: usize, byte_length: usize) -> Span {
+    Span { start: byte_start, length: byte_length, byte_start, byte_length }
+}
+
+#[test]
+fn render() {
+    let map = SourceMap::new("let a = 1;\nlet b = ;\n");
+    let diagnostic = Diagnostic::error("expected expression")
+        .with_primary(span(19, 1), Some("expected expression here".into()))
+        .with_secondary(span(11, 3), None)
+        .with_note("a statement needs a value");
+
+    let rendered = Renderer::new(&map).with_file_name("main.rs").render(&diagnostic);
+    assert_eq!(rendered, "\
+error: expected expression
+ --> main.rs:2:9
+  |
+2 | let b = ;
+  |         ^ expected expression here
+  | ---
+  = note: a statement needs a value
+");
+}
+
+#[test]
+fn render_multiple_lines() {
+    let map = SourceMap::new("a\n\tb\nc\nd\ne");
+    let diagnostic = Diagnostic::warning("unused")
+        .with_primary(span(2, 4), Some("here".into()))
+        .with_secondary(span(9, 1), None);
+
+    let rendered = Renderer::new(&map).render(&diagnostic);
+    assert_eq!(rendered, "\
+warning: unused
+ --> 2:1
+  |
+2 |     b
+  | ^^^^^
+3 | c
+  | ^ here
+...
+5 | e
+  | -
+");
+}
+
+#[test]
+fn from_syntax_error() {
+    let map = SourceMap::new("(1 + }");
+    let error = SyntaxError::<&str>::new(ErrorKind::Unexpected, span(5, 1))
+        .with_found(Found::Char('}'))
+        .with_expected([Expected::Node("expression".into())]);
+
+    let rendered = Renderer::new(&map).render(&Diagnostic::from(&error));
+    assert_eq!(rendered, "\
+error: unexpected '}', expected expression
+ --> 1:6
+  |
+1 | (1 + }
+  |      ^ unexpected '}'
+");
+}
@@ -1,34 +1,313 @@
+use alloc::borrow::Cow;
+use core::fmt::{self, Display, Formatter};
+use bytestring::ByteString;
+use indexmap::IndexSet;
 use thiserror::Error;
 use crate::span::{ArithmeticOverflow, Span};
 
 #[cfg(test)]
 mod test;
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Clone, Error, PartialEq)]
 pub enum ErrorKind<SpecificError> {
     Specific(SpecificError),
-    Unexpected
+    Unexpected,
+    /// Input was left over after everything that should have been parsed.
+    TrailingInput
 }
 
-#[derive(Debug, Error, PartialEq)]
+/// Something that a parser expected to find where a syntax error occurred.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expected {
+    Char(char),
+    String(Cow<'static, str>),
+    /// Name of a token, such as "identifier".
+    Token(Cow<'static, str>),
+    /// Name of a node, such as "expression".
+    Node(Cow<'static, str>),
+    EndOfInput
+}
+
+/// What a parser found where a syntax error occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Found {
+    Char(char),
+    String(ByteString),
+    EndOfInput
+}
+
+#[derive(Debug, Clone, Error, PartialEq)]
 pub struct SyntaxError<SpecificError> {
     kind: ErrorKind<SpecificError>,
-    span: Span
+    span: Span,
+    found: Option<Found>,
+    expected: IndexSet<Expected>
+}
+
+/// A node parsed with [crate::Parser::parse_guarded] started parsing again at the position it was 
+/// already being parsed at, which would recurse forever.
+/// 
+/// # Usage
+/// Left-recursive nodes must be parsed with [crate::Parser::parse_left_recursive].
+#[derive(Debug, Clone, Error, PartialEq)]
+#[error("Node `{node}` is left-recursive but was not parsed as left-recursive")]
+pub struct LeftRecursion {
+    /// Name of the type of the node.
+    pub node: &'static str,
+    pub span: Span
+}
+
+/// A limit set on a parser with [crate::Parser::with_limits].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Depth,
+    Steps
 }
 
-#[derive(Debug, Error, PartialEq)]
+/// Parsing stopped because it would have exceeded a limit.
+#[derive(Debug, Clone, Error, PartialEq)]
+#[error("Parsing exceeded the {limit} limit")]
+pub struct LimitExceeded {
+    pub limit: Limit,
+    /// Where the node that would have exceeded the limit started.
+    pub span: Span
+}
+
+/// Parsing stopped because it was cancelled with [crate::Parser::with_cancellation].
+#[derive(Debug, Clone, Error, PartialEq)]
+#[error("Parsing was cancelled")]
+pub struct Cancelled {
+    /// Where the parser was when it found out it was cancelled.
+    pub span: Span
+}
+
+#[derive(Debug, Clone, Error, PartialEq)]
 pub enum Error<SpecificError> {
     #[error("Cannot derive the parser because it would cause the span to overflow")]
     ArithmeticOverflow(ArithmeticOverflow),
     #[error("Syntax error in parsing content")]
     SyntaxError(SyntaxError<SpecificError>),
+    #[error("Cannot parse a left-recursive node")]
+    LeftRecursion(LeftRecursion),
+    #[error("Parsing exceeded a limit")]
+    LimitExceeded(LimitExceeded),
+    #[error("Parsing was cancelled")]
+    Cancelled(Cancelled)
+}
+
+impl<SpecificError> SyntaxError<SpecificError> {
+    pub fn new(kind: ErrorKind<SpecificError>, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            found: None,
+            expected: IndexSet::new()
+        }
+    }
+
+    /// Set what was found where the error occurred.
+    pub fn with_found(mut self, found: Found) -> Self {
+        self.found = Some(found);
+        self
+    }
+
+    /// Add to the set of things that were expected where the error occurred.
+    pub fn with_expected(mut self, expected: impl IntoIterator<Item = Expected>) -> Self {
+        self.expected.extend(expected);
+        self
+    }
+
+    pub fn kind(&self) -> &ErrorKind<SpecificError> {
+        &self.kind
+    }
+
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn found(&self) -> Option<&Found> {
+        self.found.as_ref()
+    }
+
+    pub fn expected(&self) -> &IndexSet<Expected> {
+        &self.expected
+    }
+
+    /// Merge the position of another error into this one, keeping whichever error occurred
+    /// furthest into the source.
+    ///
+    /// # Usage
+    /// Used to combine the errors of alternatives that were tried at the same position. If both
+    /// errors occurred at the same position, the expected sets are combined because any of the
+    /// alternatives could have continued from there.
+    pub fn merge_furthest<Other>(&mut self, other: &SyntaxError<Other>) {
+        if other.span.byte_start > self.span.byte_start {
+            self.span = other.span;
+            self.found.clone_from(&other.found);
+            self.expected.clone_from(&other.expected);
+        } else if other.span.byte_start == self.span.byte_start {
+            if self.found.is_none() { self.found.clone_from(&other.found) }
+            if self.span.byte_length == 0 { self.span = other.span }
+            self.expected.extend(other.expected.iter().cloned());
+        }
+    }
+
+    /// Keep whichever of two errors occurred furthest into the source, combining their expected 
+    /// sets if they occurred at the same position.
+    /// 
+    /// # Usage
+    /// Used to pick the error to report after every alternative failed. The specific error of the 
+    /// first error at the furthest position is kept.
+    pub fn furthest(mut self, mut other: Self) -> Self {
+        if other.span.byte_start > self.span.byte_start {
+            other.merge_furthest(&self);
+            other
+        } else {
+            self.merge_furthest(&other);
+            self
+        }
+    }
+
+    /// Replace the kind of error while keeping where it occurred.
+    pub fn with_kind<Other>(self, kind: ErrorKind<Other>) -> SyntaxError<Other> {
+        SyntaxError {
+            kind,
+            span: self.span,
+            found: self.found,
+            expected: self.expected
+        }
+    }
+
+    /// Convert the specific error, if there is one.
+    pub fn map<Other>(self, map: impl FnOnce(SpecificError) -> Other) -> SyntaxError<Other> {
+        let kind = match self.kind {
+            ErrorKind::Specific(error) => ErrorKind::Specific(map(error)),
+            ErrorKind::Unexpected => ErrorKind::Unexpected,
+            ErrorKind::TrailingInput => ErrorKind::TrailingInput
+        };
+        SyntaxError {
+            kind,
+            span: self.span,
+            found: self.found,
+            expected: self.expected
+        }
+    }
 }
 
 impl<SpecificError> Error<SpecificError> {
     pub fn new_syntax_temp() -> Self {
-        Self::SyntaxError(SyntaxError {
-            kind: ErrorKind::Unexpected,
-            span: Span::default()
-        })
+        Self::SyntaxError(SyntaxError::new(ErrorKind::Unexpected, Span::default()))
+    }
+
+    /// Convert the specific error, if there is one.
+    pub fn map<Other>(self, map: impl FnOnce(SpecificError) -> Other) -> Error<Other> {
+        match self {
+            Self::ArithmeticOverflow(overflow) => Error::ArithmeticOverflow(overflow),
+            Self::LeftRecursion(recursion) => Error::LeftRecursion(recursion),
+            Self::LimitExceeded(exceeded) => Error::LimitExceeded(exceeded),
+            Self::Cancelled(cancelled) => Error::Cancelled(cancelled),
+            Self::SyntaxError(error) => Error::SyntaxError(error.map(map))
+        }
+    }
+    
+    /// Convert an error that is not a syntax error to any specific error type, or return the 
+    /// syntax error.
+    /// 
+    /// # Usage
+    /// Errors other than syntax errors stop parsing entirely, so nodes that recover from syntax 
+    /// errors, such as alternatives and repetitions, use this to pass them on instead.
+    pub fn fatal<Other>(self) -> Result<Error<Other>, Self> {
+        match self {
+            Self::ArithmeticOverflow(overflow) => Ok(Error::ArithmeticOverflow(overflow)),
+            Self::LeftRecursion(recursion) => Ok(Error::LeftRecursion(recursion)),
+            Self::LimitExceeded(exceeded) => Ok(Error::LimitExceeded(exceeded)),
+            Self::Cancelled(cancelled) => Ok(Error::Cancelled(cancelled)),
+            Self::SyntaxError(error) => Err(Self::SyntaxError(error))
+        }
+    }
+
+    /// The syntax error, if this is one.
+    pub fn syntax(&self) -> Option<&SyntaxError<SpecificError>> {
+        match self {
+            Self::SyntaxError(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Char(char) => write!(f, "{char:?}"),
+            Self::String(string) => write!(f, "{string:?}"),
+            Self::Token(name) | Self::Node(name) => write!(f, "{name}"),
+            Self::EndOfInput => write!(f, "end of input")
+        }
     }
-}
\ No newline at end of file
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Depth => write!(f, "nesting depth"),
+            Self::Steps => write!(f, "step")
+        }
+    }
+}
+
+impl Display for Found {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Char(char) => write!(f, "{char:?}"),
+            Self::String(string) => write!(f, "{:?}", &**string),
+            Self::EndOfInput => write!(f, "end of input")
+        }
+    }
+}
+
+impl<SpecificError> SyntaxError<SpecificError> {
+    /// Describe what was found and expected where the error occurred, leaving out the specific 
+    /// error.
+    /// 
+    /// # Usage
+    /// Used to describe errors whose specific error cannot be displayed.
+    pub fn position_message(&self) -> String {
+        let mut message = String::new();
+        // Writing into a string cannot fail.
+        let _ = self.write_found(&mut message).and_then(|_| self.write_expected(&mut message));
+        message
+    }
+
+    fn write_found(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        match &self.found {
+            Some(found) => write!(f, "unexpected {found}"),
+            None => write!(f, "unexpected input")
+        }
+    }
+
+    fn write_expected(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let count = self.expected.len();
+        for (index, expected) in self.expected.iter().enumerate() {
+            let separator = match index {
+                0 => ", expected ",
+                _ if index + 1 == count => " or ",
+                _ => ", "
+            };
+            write!(f, "{separator}{expected}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<SpecificError: Display> Display for SyntaxError<SpecificError> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::Specific(error) => write!(f, "{error}")?,
+            ErrorKind::Unexpected => self.write_found(f)?,
+            ErrorKind::TrailingInput => write!(f, "unexpected trailing input")?
+        }
+
+        self.write_expected(f)
+    }
+}
@@ -0,0 +1,29 @@
+use crate::error::{ErrorKind, Expected, Found, SyntaxError};
+use crate::span::Span;
+
+fn at(byte_start: usize) -> Span {
+    Span { start: byte_start, length: 1, byte_start, byte_length: 1 }
+}
+
+#[test]
+fn merge_furthest() {
+    let mut error = SyntaxError::<()>::new(ErrorKind::Unexpected, at(0))
+        .with_expected([Expected::Char('a')]);
+
+    error.merge_furthest(&SyntaxError::<()>::new(ErrorKind::Unexpected, at(0)).with_expected([Expected::Char('b')]));
+    assert_eq!(error.expected().len(), 2);
+
+    error.merge_furthest(&SyntaxError::<()>::new(ErrorKind::Unexpected, at(2)).with_found(Found::EndOfInput));
+    assert_eq!(error.span().byte_start, 2);
+    assert_eq!(error.found(), Some(&Found::EndOfInput));
+    assert!(error.expected().is_empty());
+}
+
+#[test]
+fn display() {
+    let error = SyntaxError::<&str>::new(ErrorKind::Unexpected, at(0))
+        .with_found(Found::Char('}'))
+        .with_expected([Expected::Char(';'), Expected::String("else".into()), Expected::Node("expression".into())]);
+
+    assert_eq!(error.to_string(), "unexpected '}', expected ';', \"else\" or expression");
+}
@@ -0,0 +1,304 @@
+use core::marker::PhantomData;
+use bytestring::ByteString;
+use indexmap::IndexMap;
+use thiserror::Error;
+use crate::{Node, Parsable, Parser};
+use crate::error::{Error, Expected, SyntaxError};
+use crate::sync::Shared;
+
+mod syntax;
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum GrammarError {
+    #[error("Rule `{0}` is referenced but never defined")]
+    UndefinedRule(ByteString),
+    #[error("Rule `{0}` is defined more than once")]
+    DuplicateRule(ByteString),
+    #[error("Unknown escape sequence")]
+    InvalidEscape
+}
+
+/// Notation a grammar is described in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    /// Parsing expressions, as described on [Grammar].
+    #[default]
+    Peg,
+    /// Extended Backus-Naur form, where items of a sequence are separated by `,` or whitespace,
+    /// `[a]` makes an expression optional, `{a}` repeats it and comments are written as
+    /// `(* comment *)`. Alternatives separated by `|` are still tried in order, as in PEG, and
+    /// there are no character classes, lookaheads or `.`.
+    Ebnf
+}
+
+/// Expression of a rule, interpreted every time the rule is parsed.
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Choice(Vec<Expression>),
+    Sequence(Vec<Expression>),
+    Many(Box<Expression>),
+    Many1(Box<Expression>),
+    Optional(Box<Expression>),
+    Peek(Box<Expression>),
+    Not(Box<Expression>),
+    Literal(String),
+    Class(Class),
+    Any,
+    /// Index of the rule in the grammar.
+    Rule(usize)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Class {
+    negated: bool,
+    ranges: Vec<(char, char)>,
+    /// The class as it was written, used when a character does not match it.
+    description: String
+}
+
+/// Grammar read at runtime from a description of its rules.
+///
+/// # Usage
+/// Rules are written as `name = expression;`, where `<-` or `::=` can be used in place of `=` and
+/// the `;` can be left out. Expressions are parsing expressions:
+/// - `"text"` or `'text'` expects the text and `.` expects any character.
+/// - `[a-z_]` expects a character in the class, and `[^"]` one that is not.
+/// - `name` parses another rule.
+/// - `a b` parses in sequence and `a / b` or `a | b` tries each alternative in order, rewinding
+///   between them.
+/// - `a*`, `a+` and `a?` repeat or make an expression optional, and `(a)` groups.
+/// - `&a` and `!a` look ahead without consuming anything.
+///
+/// Comments start with `#` or `//` and run to the end of the line. Grammars can also be written
+/// in EBNF with [Self::with_dialect]. Cloning a grammar is cheap, as the rules are shared.
+#[derive(Debug, Clone)]
+pub struct Grammar {
+    rules: Shared<IndexMap<ByteString, Expression>>
+}
+
+/// Rule of a grammar, given as the data when parsing a [Tree].
+#[derive(Debug, Clone)]
+pub struct Rule {
+    grammar: Grammar,
+    index: usize
+}
+
+/// Generic tree produced by parsing a rule of a [Grammar].
+///
+/// # Usage
+/// Every rule the rule parsed becomes a child, in order. Everything else it parsed is only
+/// available through the span of its node.
+#[derive(Debug, Clone)]
+pub struct Tree<Token = ()> {
+    pub rule: ByteString,
+    pub children: Vec<Node<Tree<Token>>>,
+    token: PhantomData<Token>
+}
+
+impl Grammar {
+    /// Read a grammar from the description of its rules.
+    ///
+    /// # Error
+    /// Syntax errors in the description are returned as they would be for any other source, and
+    /// so are rules that are referenced without being defined or that are defined twice.
+    pub fn new(description: &str) -> Result<Self, Error<GrammarError>> {
+        Self::with_dialect(description, Dialect::Peg)
+    }
+
+    /// Read a grammar from the description of its rules in a [Dialect].
+    ///
+    /// # Error
+    /// Fails as [Self::new] does.
+    pub fn with_dialect(description: &str, dialect: Dialect) -> Result<Self, Error<GrammarError>> {
+        let rules = syntax::read(description, dialect)?;
+        Ok(Self { rules: Shared::new(rules) })
+    }
+
+    /// The rule with a name, if the grammar defines it.
+    pub fn rule(&self, name: &str) -> Option<Rule> {
+        let index = self.rules.get_index_of(name)?;
+        Some(Rule { grammar: self.clone(), index })
+    }
+
+    /// Names of the rules, in the order they first appear in the description, including references 
+    /// from other rules.
+    pub fn rules(&self) -> impl Iterator<Item = &str> {
+        self.rules.keys().map(|name| &**name)
+    }
+
+    fn evaluate<Token: 'static>(&self, expression: &Expression, parser: &mut Parser<Token>, children: &mut Vec<Node<Tree<Token>>>) -> Result<(), Error<()>> {
+        match expression {
+            Expression::Sequence(items) => {
+                for item in items {
+                    self.evaluate(item, parser, children)?;
+                }
+                Ok(())
+            },
+            Expression::Choice(alternatives) => {
+                let checkpoint = parser.checkpoint();
+                let length = children.len();
+                let mut failure: Option<SyntaxError<()>> = None;
+
+                for alternative in alternatives {
+                    match self.evaluate(alternative, parser, children) {
+                        Ok(()) => return Ok(()),
+                        Err(Error::SyntaxError(error)) => failure = Some(match failure {
+                            Some(failure) => failure.furthest(error),
+                            None => error
+                        }),
+                        Err(error) => return Err(error)
+                    }
+
+                    parser.rewind(checkpoint.clone());
+                    children.truncate(length);
+                }
+
+                Err(failure.map_or_else(|| parser.unexpected(), Error::SyntaxError))
+            },
+            Expression::Optional(item) => {
+                let checkpoint = parser.checkpoint();
+                let length = children.len();
+
+                match self.evaluate(item, parser, children) {
+                    Err(Error::SyntaxError(_)) => {
+                        parser.rewind(checkpoint);
+                        children.truncate(length);
+                        Ok(())
+                    },
+                    result => result
+                }
+            },
+            Expression::Many(item) | Expression::Many1(item) => {
+                let minimum = usize::from(matches!(expression, Expression::Many1(_)));
+                repeat(parser, minimum, |parser| {
+                    let length = children.len();
+                    let result = self.evaluate(item, parser, children);
+                    if result.is_err() { children.truncate(length) }
+                    result
+                })
+            },
+            Expression::Peek(item) => {
+                let checkpoint = parser.checkpoint();
+                let length = children.len();
+                let result = self.evaluate(item, parser, children);
+
+                parser.rewind(checkpoint);
+                children.truncate(length);
+                result
+            },
+            Expression::Not(item) => not(parser, |parser| self.evaluate(item, parser, &mut Vec::new())),
+            Expression::Literal(literal) => {
+                parser.expect_str(literal).map_err(|_| parser.expected([Expected::String(literal.clone().into())]))?;
+                Ok(())
+            },
+            Expression::Class(class) => match parser.peek() {
+                Some(character) if class.matches(character) => {
+                    let _ = parser.expect_char(character);
+                    Ok(())
+                },
+                _ => Err(parser.expected([Expected::Token(class.description.clone().into())]))
+            },
+            Expression::Any => match parser.peek() {
+                Some(character) => {
+                    let _ = parser.expect_char(character);
+                    Ok(())
+                },
+                None => Err(parser.expected([Expected::Token("any character".into())]))
+            },
+            Expression::Rule(index) => {
+                let mut rule = Rule { grammar: self.clone(), index: *index };
+                children.push(parser.parse_guarded::<Tree<Token>>(&mut rule, *index)?);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Parse an item of a grammar as often as it matches, failing if it matched fewer than `minimum` 
+/// times. This is how both [Grammar] and the `grammar!` macro repeat expressions.
+/// 
+/// # Usage
+/// The parser is rewound to where the attempt that failed started, but the item has to undo 
+/// anything else it recorded itself. Repetition ends early once an item parses without consuming 
+/// anything, because it would be parsed again at the same position forever.
+/// 
+/// # Error
+/// Errors other than syntax errors are returned as soon as an item fails with one.
+pub fn repeat<Token, SpecificError>(parser: &mut Parser<Token>, minimum: usize, mut item: impl FnMut(&mut Parser<Token>) -> Result<(), Error<SpecificError>>) -> Result<(), Error<SpecificError>> {
+    let mut count = 0;
+
+    loop {
+        let checkpoint = parser.checkpoint();
+        let start = parser.position();
+
+        match item(parser) {
+            Ok(()) => {
+                count += 1;
+                if parser.position() == start { return Ok(()) }
+            },
+            Err(Error::SyntaxError(error)) => {
+                parser.rewind(checkpoint);
+                return if count < minimum { Err(Error::SyntaxError(error)) } else { Ok(()) };
+            },
+            Err(error) => return Err(error)
+        }
+    }
+}
+
+/// Negative lookahead of an item of a grammar, succeeding without consuming anything only if the 
+/// item fails to parse. This is how both [Grammar] and the `grammar!` macro parse `!` expressions.
+/// 
+/// # Usage
+/// Failing is the desired outcome, so the item is parsed with [Parser::detach_failures] and what 
+/// it tried is not recorded as expected.
+/// 
+/// # Error
+/// Fails with an unexpected error at the start of the item if it parses, or with the error of the 
+/// item if it is not a syntax error.
+pub fn not<Token, SpecificError>(parser: &mut Parser<Token>, item: impl FnOnce(&mut Parser<Token>) -> Result<(), Error<SpecificError>>) -> Result<(), Error<SpecificError>> {
+    let checkpoint = parser.checkpoint();
+    let result = item(&mut parser.detach_failures());
+    parser.rewind(checkpoint);
+
+    match result {
+        Ok(()) => Err(parser.unexpected()),
+        Err(Error::SyntaxError(_)) => Ok(()),
+        Err(error) => Err(error)
+    }
+}
+
+impl Class {
+    fn matches(&self, character: char) -> bool {
+        let contained = self.ranges.iter().any(|&(start, end)| (start..=end).contains(&character));
+        contained != self.negated
+    }
+}
+
+impl Rule {
+    pub fn name(&self) -> &str {
+        self.grammar.rules.get_index(self.index).map_or("", |(name, _)| name)
+    }
+}
+
+impl<Token: 'static> Parsable for Tree<Token> {
+    type Error = ();
+    type Token = Token;
+    type Data = Rule;
+
+    fn parse(parser: &mut Parser<Self::Token>, rule: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let (name, expression) = rule.grammar.rules
+            .get_index(rule.index)
+            .expect("rules are only created for indices in their grammar");
+
+        let mut children = Vec::new();
+        rule.grammar.evaluate(expression, parser, &mut children)?;
+
+        Ok(Self {
+            rule: name.clone(),
+            children,
+            token: PhantomData
+        })
+    }
+}
@@ -0,0 +1,307 @@
+//! Reading the description of a grammar, which is itself parsed with a [Parser].
+
+use bytestring::ByteString;
+use indexmap::IndexMap;
+use crate::{Parsable, Parser};
+use crate::error::{Error, ErrorKind, Expected, SyntaxError};
+use crate::span::Span;
+use super::{Class, Dialect, Expression, GrammarError};
+
+/// Separators between the name of a rule and its expression.
+const SEPARATORS: [&str; 3] = ["::=", "<-", "="];
+
+/// Rules defined so far while reading a grammar.
+#[derive(Debug)]
+struct Definitions {
+    dialect: Dialect,
+    /// Every rule that was either defined or referenced, in the order they first appeared.
+    rules: IndexMap<ByteString, Option<Expression>>,
+    /// Where each rule was first referenced, used to report rules that are never defined.
+    references: IndexMap<usize, Span>
+}
+
+/// Name of a rule.
+#[derive(Debug)]
+struct Name;
+
+/// Definition of a rule, which is added to the definitions.
+#[derive(Debug)]
+struct Definition;
+
+/// Read the rules of a grammar, resolving references to rules by their index.
+pub(super) fn read(description: &str, dialect: Dialect) -> Result<IndexMap<ByteString, Expression>, Error<GrammarError>> {
+    let mut parser = Parser::<()>::new(description);
+    let mut definitions = Definitions {
+        dialect,
+        rules: IndexMap::new(),
+        references: IndexMap::new()
+    };
+
+    while {
+        blank(&mut parser, dialect);
+        parser.peek().is_some()
+    } {
+        parser.parse::<Definition>(&mut definitions)?;
+    }
+
+    if let Some((index, span)) = definitions.references.iter().find(|(index, _)| definitions.rules[**index].is_none()) {
+        let (name, _) = definitions.rules.get_index(*index).expect("references are to defined or referenced rules");
+        return Err(Error::SyntaxError(SyntaxError::new(ErrorKind::Specific(GrammarError::UndefinedRule(name.clone())), *span)));
+    }
+
+    Ok(definitions.rules
+        .into_iter()
+        .filter_map(|(name, expression)| Some((name, expression?)))
+        .collect())
+}
+
+impl Parsable for Name {
+    type Error = GrammarError;
+    type Token = ();
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, _: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        if !parser.peek().is_some_and(|character| character.is_alphabetic() || character == '_') {
+            return Err(parser.expected([Expected::Node("rule name".into())]));
+        }
+
+        parser.parse_while(|character| character.is_alphanumeric() || character == '_');
+        Ok(Self)
+    }
+}
+
+impl Parsable for Definition {
+    type Error = GrammarError;
+    type Token = ();
+    type Data = Definitions;
+
+    fn parse(parser: &mut Parser<Self::Token>, definitions: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let name = parser.parse::<Name>(&mut ())?;
+        let rule = definitions.rules.entry(name.source.clone());
+        let index = rule.index();
+        if rule.or_default().is_some() {
+            let error = GrammarError::DuplicateRule(name.source.clone());
+            return Err(Error::SyntaxError(SyntaxError::new(ErrorKind::Specific(error), *name.span())));
+        }
+
+        blank(parser, definitions.dialect);
+        parser.expect_any_str(&SEPARATORS).map_err(|_| parser.expected(SEPARATORS.map(|separator| Expected::String(separator.into()))))?;
+
+        let expression = choice(parser, definitions)?;
+        blank(parser, definitions.dialect);
+        let _ = parser.expect_char(';');
+
+        definitions.rules[index] = Some(expression);
+        Ok(Self)
+    }
+}
+
+/// Skip whitespace and comments, which are `(* comment *)` in EBNF and run from `#` or `//` to
+/// the end of the line in PEG.
+fn blank(parser: &mut Parser<()>, dialect: Dialect) {
+    loop {
+        parser.parse_while(char::is_whitespace);
+
+        match dialect {
+            Dialect::Peg if parser.expect_char('#').is_ok() || parser.expect_str("//").is_ok() => {
+                parser.parse_while(|character| character != '\n');
+            },
+            Dialect::Ebnf if parser.expect_str("(*").is_ok() => {
+                while !parser.remaining().is_empty() && parser.expect_str("*)").is_err() {
+                    parser.parse_while(|character| character != '*');
+                    if !parser.remaining().starts_with("*)") { let _ = parser.expect_char('*'); }
+                }
+            },
+            _ => return
+        }
+    }
+}
+
+/// Whether the next thing in the description is the start of another rule, which ends the
+/// expression of the current one when it is not ended with a `;`.
+fn at_definition(parser: &Parser<()>, dialect: Dialect) -> bool {
+    let mut lookahead = parser.detach_failures();
+    if lookahead.parse::<Name>(&mut ()).is_err() { return false }
+
+    blank(&mut lookahead, dialect);
+    lookahead.expect_any_str(&SEPARATORS).is_ok()
+}
+
+fn choice(parser: &mut Parser<()>, definitions: &mut Definitions) -> Result<Expression, Error<GrammarError>> {
+    let mut alternatives = vec![sequence(parser, definitions)?];
+
+    while {
+        blank(parser, definitions.dialect);
+        parser.expect_char('|').is_ok() || (definitions.dialect == Dialect::Peg && parser.expect_char('/').is_ok())
+    } {
+        alternatives.push(sequence(parser, definitions)?);
+    }
+
+    Ok(if alternatives.len() == 1 { alternatives.remove(0) } else { Expression::Choice(alternatives) })
+}
+
+fn sequence(parser: &mut Parser<()>, definitions: &mut Definitions) -> Result<Expression, Error<GrammarError>> {
+    let mut items = Vec::new();
+
+    let dialect = definitions.dialect;
+
+    loop {
+        blank(parser, dialect);
+        let ended = match dialect {
+            Dialect::Peg => matches!(parser.peek(), None | Some('/' | '|' | ')' | ';')),
+            Dialect::Ebnf => matches!(parser.peek(), None | Some('|' | ')' | ']' | '}' | ';'))
+        };
+        if ended || (!items.is_empty() && at_definition(parser, dialect)) { break }
+
+        // Items can be separated by `,` in EBNF, which then has to be followed by another item.
+        if dialect == Dialect::Ebnf && !items.is_empty() && parser.expect_char(',').is_ok() {
+            blank(parser, dialect);
+        }
+
+        items.push(prefixed(parser, definitions)?);
+    }
+
+    match items.len() {
+        0 => Err(parser.expected([Expected::Node("expression".into())])),
+        1 => Ok(items.remove(0)),
+        _ => Ok(Expression::Sequence(items))
+    }
+}
+
+fn prefixed(parser: &mut Parser<()>, definitions: &mut Definitions) -> Result<Expression, Error<GrammarError>> {
+    let dialect = definitions.dialect;
+    if dialect == Dialect::Ebnf { return primary(parser, definitions) }
+
+    if parser.expect_char('&').is_ok() {
+        blank(parser, dialect);
+        Ok(Expression::Peek(Box::new(suffixed(parser, definitions)?)))
+    } else if parser.expect_char('!').is_ok() {
+        blank(parser, dialect);
+        Ok(Expression::Not(Box::new(suffixed(parser, definitions)?)))
+    } else {
+        suffixed(parser, definitions)
+    }
+}
+
+fn suffixed(parser: &mut Parser<()>, definitions: &mut Definitions) -> Result<Expression, Error<GrammarError>> {
+    let mut expression = primary(parser, definitions)?;
+
+    loop {
+        blank(parser, definitions.dialect);
+        expression = if parser.expect_char('*').is_ok() {
+            Expression::Many(Box::new(expression))
+        } else if parser.expect_char('+').is_ok() {
+            Expression::Many1(Box::new(expression))
+        } else if parser.expect_char('?').is_ok() {
+            Expression::Optional(Box::new(expression))
+        } else {
+            return Ok(expression);
+        };
+    }
+}
+
+fn primary(parser: &mut Parser<()>, definitions: &mut Definitions) -> Result<Expression, Error<GrammarError>> {
+    let dialect = definitions.dialect;
+
+    match parser.peek() {
+        Some('(') => group(parser, definitions, ('(', ')')),
+        Some(quote @ ('"' | '\'')) => {
+            let _ = parser.expect_char(quote);
+            literal(parser, quote).map(Expression::Literal)
+        },
+        Some('[') if dialect == Dialect::Ebnf => Ok(Expression::Optional(Box::new(group(parser, definitions, ('[', ']'))?))),
+        Some('{') if dialect == Dialect::Ebnf => Ok(Expression::Many(Box::new(group(parser, definitions, ('{', '}'))?))),
+        Some('[') => class(parser).map(Expression::Class),
+        Some('.') if dialect == Dialect::Peg => {
+            let _ = parser.expect_char('.');
+            Ok(Expression::Any)
+        },
+        _ => {
+            let name = parser.parse::<Name>(&mut ()).map_err(|_| parser.expected([Expected::Node("expression".into())]))?;
+            let rule = definitions.rules.entry(name.source.clone());
+            let index = rule.index();
+            rule.or_default();
+
+            definitions.references.entry(index).or_insert(*name.span());
+            Ok(Expression::Rule(index))
+        }
+    }
+}
+
+/// Choice between brackets, where the opening bracket is next.
+fn group(parser: &mut Parser<()>, definitions: &mut Definitions, (open, close): (char, char)) -> Result<Expression, Error<GrammarError>> {
+    let _ = parser.expect_char(open);
+    let expression = choice(parser, definitions)?;
+    blank(parser, definitions.dialect);
+    parser.expect_char(close).map_err(|_| parser.expected([Expected::Char(close)]))?;
+    Ok(expression)
+}
+
+fn literal(parser: &mut Parser<()>, quote: char) -> Result<String, Error<GrammarError>> {
+    let mut literal = String::new();
+
+    loop {
+        match parser.peek() {
+            Some(character) if character == quote => {
+                let _ = parser.expect_char(quote);
+                return Ok(literal);
+            },
+            Some(_) => literal.push(character(parser)?),
+            None => return Err(parser.expected([Expected::Char(quote)]))
+        }
+    }
+}
+
+fn class(parser: &mut Parser<()>) -> Result<Class, Error<GrammarError>> {
+    let start = parser.position();
+    let _ = parser.expect_char('[');
+    let negated = parser.expect_char('^').is_ok();
+    let mut ranges = Vec::new();
+
+    loop {
+        match parser.peek() {
+            Some(']') => break,
+            Some(_) => {},
+            None => return Err(parser.expected([Expected::Char(']')]))
+        }
+
+        let first = character(parser)?;
+        let checkpoint = parser.checkpoint();
+        // A `-` at the end of the class is the character itself rather than a range.
+        let last = if parser.expect_char('-').is_ok() && !matches!(parser.peek(), Some(']') | None) {
+            character(parser)?
+        } else {
+            parser.rewind(checkpoint);
+            first
+        };
+        ranges.push((first, last));
+    }
+
+    let _ = parser.expect_char(']');
+    Ok(Class {
+        negated,
+        ranges,
+        description: parser.source()[start..parser.position()].to_owned()
+    })
+}
+
+/// Character in a literal or class, which may be escaped with a `\`.
+fn character(parser: &mut Parser<()>) -> Result<char, Error<GrammarError>> {
+    let escaped = parser.expect_char('\\').is_ok();
+    let Some(next) = parser.peek() else {
+        return Err(parser.expected([Expected::Token("character".into())]));
+    };
+
+    let character = match next {
+        _ if !escaped => next,
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        '0' => '\0',
+        '\\' | '"' | '\'' | '[' | ']' | '-' | '^' => next,
+        _ => return Err(parser.specific_error(GrammarError::InvalidEscape))
+    };
+
+    let _ = parser.expect_char(next);
+    Ok(character)
+}
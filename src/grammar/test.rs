@@ -0,0 +1,97 @@
+use crate::Parser;
+use crate::error::{Error, ErrorKind, Expected};
+use super::{Dialect, Grammar, GrammarError, Tree};
+
+const ARITHMETIC: &str = r#"
+    # Sums of numbers, in PEG style.
+    sum <- number (space operator space number)* !.
+    operator <- "+" / '-'
+    number <- [0-9]+
+    space <- [ \t]*
+
+    // Rules can also be defined with `::=` and alternatives separated with `|`.
+    list ::= "[" (number (", " number)*)? "]";
+"#;
+
+fn rules(tree: &Tree) -> Vec<&str> {
+    tree.children.iter().map(|child| &*child.rule).collect()
+}
+
+#[test]
+fn parse() {
+    let grammar = Grammar::new(ARITHMETIC).unwrap();
+    assert_eq!(grammar.rules().collect::<Vec<_>>(), ["sum", "number", "space", "operator", "list"]);
+
+    let mut parser = Parser::<()>::new("1 + 23 - 4");
+    let tree = parser.parse::<Tree>(&mut grammar.rule("sum").unwrap()).unwrap();
+    assert_eq!(&*tree.rule, "sum");
+    assert_eq!(rules(&tree), ["number", "space", "operator", "space", "number", "space", "operator", "space", "number"]);
+    assert_eq!(tree.children[4].slice(), "23");
+    assert_eq!(tree.children[4].span().byte_start, 4);
+
+    let mut parser = Parser::<()>::new("[1, 2]");
+    let tree = parser.parse::<Tree>(&mut grammar.rule("list").unwrap()).unwrap();
+    assert_eq!(rules(&tree), ["number", "number"]);
+}
+
+#[test]
+fn ebnf() {
+    let grammar = Grammar::with_dialect(r#"
+        (* Lists of digits, in EBNF. *)
+        list = "[", [digit, {", ", digit}], "]";
+        digit = "0" | "1" | "2";
+    "#, Dialect::Ebnf).unwrap();
+
+    let mut parser = Parser::<()>::new("[0, 1, 2]");
+    let tree = parser.parse::<Tree>(&mut grammar.rule("list").unwrap()).unwrap();
+    assert_eq!(rules(&tree), ["digit", "digit", "digit"]);
+
+    let mut parser = Parser::<()>::new("[]");
+    assert!(parser.parse::<Tree>(&mut grammar.rule("list").unwrap()).unwrap().children.is_empty());
+
+    // Brackets are optional expressions rather than character classes.
+    let error = Grammar::with_dialect("a = [b-c];", Dialect::Ebnf).unwrap_err();
+    assert_eq!(error.syntax().unwrap().span().byte_start, 6);
+    assert!(Grammar::with_dialect("a = 'x',;", Dialect::Ebnf).is_err());
+}
+
+#[test]
+fn parse_errors() {
+    let grammar = Grammar::new(ARITHMETIC).unwrap();
+
+    let mut parser = Parser::<()>::new("*");
+    let error = parser.parse::<Tree>(&mut grammar.rule("operator").unwrap()).unwrap_err();
+    let error = error.syntax().unwrap();
+    assert_eq!(error.span().byte_start, 0);
+    assert!(error.expected().contains(&Expected::String("+".into())));
+    assert!(error.expected().contains(&Expected::String("-".into())));
+
+    let mut parser = Parser::<()>::new("[x");
+    let error = parser.parse::<Tree>(&mut grammar.rule("list").unwrap()).unwrap_err();
+    let error = error.syntax().unwrap();
+    assert_eq!(error.span().byte_start, 1);
+    assert!(error.expected().contains(&Expected::Token("[0-9]".into())));
+    assert!(error.expected().contains(&Expected::String("]".into())));
+
+    let grammar = Grammar::new("item = [a-z]; list = list ',' item / item").unwrap();
+    let mut parser = Parser::<()>::new("a,b");
+    let error = parser.parse::<Tree>(&mut grammar.rule("list").unwrap()).unwrap_err();
+    assert!(matches!(error, Error::LeftRecursion(_)));
+}
+
+#[test]
+fn description_errors() {
+    let error = Grammar::new("a = b;\nb = \"x\" c").unwrap_err();
+    let error = error.syntax().unwrap();
+    assert_eq!(error.kind(), &ErrorKind::Specific(GrammarError::UndefinedRule("c".into())));
+    assert_eq!(error.span().byte_start, 15);
+
+    let error = Grammar::new("a = 'x'\na = 'y'").unwrap_err();
+    assert_eq!(error.syntax().unwrap().kind(), &ErrorKind::Specific(GrammarError::DuplicateRule("a".into())));
+
+    let error = Grammar::new(r#"a = "\q""#).unwrap_err();
+    assert_eq!(error.syntax().unwrap().kind(), &ErrorKind::Specific(GrammarError::InvalidEscape));
+
+    let error = Grammar::new("a = (b").unwrap_err();
+    assert!(error.syntax().unwrap().expected().contains(&Expected::Char(')')));
+}
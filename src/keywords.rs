@@ -0,0 +1,177 @@
+use bytestring::ByteString;
+use thiserror::Error;
+use crate::string::StringsMap;
+use crate::sync::Shared;
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum KeywordTableError {
+    #[error("Keyword table ends in the middle of a keyword")]
+    Truncated,
+    #[error("Keyword table continues after its last keyword")]
+    TrailingBytes,
+    #[error("Keyword is not valid UTF-8")]
+    InvalidUtf8,
+    #[error("Token of keyword `{0}` cannot be decoded")]
+    InvalidToken(ByteString),
+    #[error("Keyword `{0}` is in the table more than once")]
+    DuplicateKeyword(ByteString)
+}
+
+/// Immutable table of strings and their tokens, such as the keywords of a language, which
+/// parsers are created from with [crate::Parser::with_keywords].
+///
+/// # Usage
+/// The table is built once, either from strings internalized beforehand or from bytes it was
+/// serialized to, and then shared by every parser created from it. Cloning it is cheap.
+///
+/// Keywords are sorted by their bytes and looked up with a binary search. A keyword's
+/// [crate::string::Symbol] is its position in the table, so it is the same for every parser
+/// created from the table and for every table deserialized from the same bytes.
+#[derive(Debug)]
+pub struct KeywordTable<Token> {
+    keywords: Shared<[(ByteString, Token)]>
+}
+
+impl<Token> KeywordTable<Token> {
+    /// Freeze internalized strings into a table.
+    pub fn freeze(strings: StringsMap<Token>) -> Self {
+        let mut keywords = strings.into_iter().collect::<Vec<_>>();
+        sort(&mut keywords);
+        Self { keywords: keywords.into() }
+    }
+
+    /// Position of a keyword in the table.
+    pub fn index_of(&self, keyword: &str) -> Option<usize> {
+        self.keywords
+            .binary_search_by(|(candidate, _)| (**candidate).cmp(keyword))
+            .ok()
+    }
+
+    pub fn get(&self, keyword: &str) -> Option<&Token> {
+        self.index_of(keyword).map(|index| &self.keywords[index].1)
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<(&ByteString, &Token)> {
+        self.keywords.get(index).map(|(keyword, token)| (keyword, token))
+    }
+
+    pub(crate) fn token(&self, index: usize) -> &Token {
+        &self.keywords[index].1
+    }
+
+    pub fn len(&self) -> usize {
+        self.keywords.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_empty()
+    }
+
+    /// Keywords and their tokens, sorted by the bytes of the keywords.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Token)> {
+        self.keywords.iter().map(|(keyword, token)| (&**keyword, token))
+    }
+
+    /// Serialize the table, encoding each token with a function.
+    ///
+    /// # Usage
+    /// The table is written as the number of keywords followed by each keyword and its encoded
+    /// token, where the number and every length is a little-endian `u32`.
+    pub fn to_bytes(&self, mut encode: impl FnMut(&Token) -> Vec<u8>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_length(&mut bytes, self.keywords.len());
+
+        for (keyword, token) in self.keywords.iter() {
+            write_length(&mut bytes, keyword.len());
+            bytes.extend_from_slice(keyword.as_bytes());
+
+            let token = encode(token);
+            write_length(&mut bytes, token.len());
+            bytes.extend_from_slice(&token);
+        }
+
+        bytes
+    }
+
+    /// Deserialize a table written by [Self::to_bytes], decoding each token with a function.
+    ///
+    /// # Error
+    /// Fails if the bytes are not a table, if a token cannot be decoded or if a keyword is in
+    /// the table more than once.
+    pub fn from_bytes(mut bytes: &[u8], mut decode: impl FnMut(&[u8]) -> Option<Token>) -> Result<Self, KeywordTableError> {
+        let count = read_length(&mut bytes)?;
+        // Every keyword takes at least 8 bytes, so a corrupt count cannot allocate much more
+        // than the bytes themselves.
+        let mut keywords = Vec::with_capacity(count.min(bytes.len() / 8));
+
+        for _ in 0..count {
+            let keyword = read_slice(&mut bytes)?;
+            let keyword = ByteString::from(core::str::from_utf8(keyword).map_err(|_| KeywordTableError::InvalidUtf8)?);
+
+            let token = read_slice(&mut bytes)?;
+            let Some(token) = decode(token) else { return Err(KeywordTableError::InvalidToken(keyword)) };
+            keywords.push((keyword, token));
+        }
+
+        if !bytes.is_empty() { return Err(KeywordTableError::TrailingBytes) }
+
+        sort(&mut keywords);
+        if let Some(pair) = keywords.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(KeywordTableError::DuplicateKeyword(pair[0].0.clone()));
+        }
+
+        Ok(Self { keywords: keywords.into() })
+    }
+}
+
+impl<Token> Clone for KeywordTable<Token> {
+    fn clone(&self) -> Self {
+        Self { keywords: self.keywords.clone() }
+    }
+}
+
+impl<Token: PartialEq> PartialEq for KeywordTable<Token> {
+    fn eq(&self, other: &Self) -> bool {
+        self.keywords == other.keywords
+    }
+}
+
+impl<Token> Default for KeywordTable<Token> {
+    fn default() -> Self {
+        Self { keywords: Vec::new().into() }
+    }
+}
+
+impl<Token> FromIterator<(ByteString, Token)> for KeywordTable<Token> {
+    /// Build a table from keywords and their tokens, keeping the last token of keywords that are
+    /// given more than once.
+    fn from_iter<Iterator: IntoIterator<Item = (ByteString, Token)>>(keywords: Iterator) -> Self {
+        Self::freeze(keywords.into_iter().collect())
+    }
+}
+
+/// Sort keywords by their bytes, which is the order of a table.
+fn sort<Token>(keywords: &mut [(ByteString, Token)]) {
+    keywords.sort_unstable_by(|left, right| left.0.cmp(&right.0));
+}
+
+fn write_length(bytes: &mut Vec<u8>, length: usize) {
+    let length = u32::try_from(length).expect("keyword tables and their keywords are shorter than `u32::MAX`");
+    bytes.extend_from_slice(&length.to_le_bytes());
+}
+
+fn read_length(bytes: &mut &[u8]) -> Result<usize, KeywordTableError> {
+    let (length, rest) = bytes.split_first_chunk::<4>().ok_or(KeywordTableError::Truncated)?;
+    *bytes = rest;
+    Ok(u32::from_le_bytes(*length) as usize)
+}
+
+fn read_slice<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], KeywordTableError> {
+    let length = read_length(bytes)?;
+    let (slice, rest) = bytes.split_at_checked(length).ok_or(KeywordTableError::Truncated)?;
+    *bytes = rest;
+    Ok(slice)
+}
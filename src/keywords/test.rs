@@ -0,0 +1,75 @@
+use crate::Parser;
+use super::{KeywordTable, KeywordTableError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Let,
+    If,
+    Identifier
+}
+
+fn encode(token: &Token) -> Vec<u8> {
+    vec![*token as u8]
+}
+
+fn decode(bytes: &[u8]) -> Option<Token> {
+    match bytes {
+        [0] => Some(Token::Let),
+        [1] => Some(Token::If),
+        [2] => Some(Token::Identifier),
+        _ => None
+    }
+}
+
+fn keywords() -> KeywordTable<Token> {
+    let mut parser = Parser::<Token>::new("");
+    parser.internalize("let", Token::Let).unwrap();
+    parser.internalize("if", Token::If).unwrap();
+    parser.freeze_strings()
+}
+
+#[test]
+fn serialize() {
+    let keywords = keywords();
+    assert_eq!(keywords.iter().collect::<Vec<_>>(), [("if", &Token::If), ("let", &Token::Let)]);
+    assert_eq!(keywords.get("let"), Some(&Token::Let));
+    assert_eq!(keywords.get("x"), None);
+
+    let bytes = keywords.to_bytes(encode);
+    assert_eq!(KeywordTable::from_bytes(&bytes, decode), Ok(keywords));
+
+    assert_eq!(KeywordTable::from_bytes(&bytes[..bytes.len() - 1], decode), Err(KeywordTableError::Truncated));
+    assert_eq!(KeywordTable::from_bytes(&[bytes.as_slice(), &[0]].concat(), decode), Err(KeywordTableError::TrailingBytes));
+    assert_eq!(KeywordTable::from_bytes(&bytes, |_| None::<Token>), Err(KeywordTableError::InvalidToken("if".into())));
+
+    let keyword = [&2u32.to_le_bytes()[..], b"if", &1u32.to_le_bytes(), &[1]].concat();
+    let duplicated = [&2u32.to_le_bytes()[..], &keyword, &keyword].concat();
+    assert_eq!(KeywordTable::from_bytes(&duplicated, decode), Err(KeywordTableError::DuplicateKeyword("if".into())));
+}
+
+#[test]
+fn parsers_share_keywords() {
+    let keywords = keywords();
+    let mut first = Parser::with_keywords("let x", keywords.clone());
+    let mut second = Parser::with_keywords("if y", keywords);
+
+    let mut word = first.parse_while(char::is_alphabetic);
+    let symbol = word.try_internalize(|_| Some(Token::Identifier)).unwrap().unwrap();
+    assert_eq!(word.token().as_deref(), Some(&Token::Let));
+    assert_eq!(second.interner().symbol("let"), Some(symbol));
+    assert!(first.internalize("let", Token::Identifier).is_err());
+
+    first.parse_while(char::is_whitespace);
+    let mut identifier = first.parse_while(char::is_alphabetic);
+    let identifier = identifier.try_internalize(|_| Some(Token::Identifier)).unwrap().unwrap();
+    assert_eq!(identifier.index(), 2);
+    assert_eq!(first.interner().resolve(identifier).as_deref(), Some("x"));
+
+    // Identifiers are only internalized by the parser that found them.
+    second.parse_while(|character| character != ' ');
+    second.parse_while(char::is_whitespace);
+    let mut identifier = second.parse_while(char::is_alphabetic);
+    identifier.try_internalize(|_| Some(Token::Identifier)).unwrap();
+    assert_eq!(second.interner().symbol("x"), None);
+    assert_eq!(second.interner().resolve(identifier.symbol().unwrap()).as_deref(), Some("y"));
+}
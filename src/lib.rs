@@ -1,31 +1,50 @@
 // #![no_std]
 #![allow(clippy::result_unit_err)]
+#![allow(clippy::result_large_err)]
 
 #![feature(round_char_boundary)]
 #![feature(new_range_api)]
 #![feature(let_chains)]
 extern crate alloc;
 extern crate core;
+// Lets code generated by the derive macro refer to this crate by name from inside of it.
+extern crate self as xfparser;
 
 pub mod error;
 pub mod span;
 pub mod string;
 pub mod node;
+pub mod combinator;
+pub mod source_map;
+pub mod diagnostic;
+pub mod grammar;
+pub mod memo;
+pub mod pratt;
+pub mod keywords;
+pub mod sync;
 
-use alloc::rc::Rc;
-use core::cell::RefCell;
-use core::iter::Peekable;
-use core::str::Chars;
+#[cfg(test)]
+mod test;
+
+use alloc::sync::Arc;
+use core::any::{type_name, TypeId};
+use core::fmt::{self, Debug, Display, Formatter};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::ops::{Deref, DerefMut, Range};
 use bytestring::ByteString;
-use indexmap::IndexMap;
-use indexmap::map::Entry;
 use thiserror::Error;
-use crate::error::Error;
+use crate::error::{Cancelled, Error, ErrorKind, Expected, Found, LeftRecursion, Limit, LimitExceeded, SyntaxError};
+use crate::diagnostic::Diagnostic;
+use crate::keywords::KeywordTable;
+use crate::memo::{Memo, MemoStats, NodeKind};
+use crate::source_map::SourceMap;
 use crate::span::{ArithmeticOverflow, Span};
 
 pub use string::String as ParserString;
-use crate::string::{Strings};
+#[cfg(feature = "derive")]
+pub use xfparser_derive::{grammar, Parsable};
+use crate::string::{Interner, Scopes, Strings, Symbol, Transaction};
+use crate::sync::{CancellationCheck, Lock, SendSync, Shared};
 
 #[derive(Debug, Clone)]
 pub struct Node<Supplementary> {
@@ -38,6 +57,10 @@ impl<S> Node<S> {
     pub fn slice(&self) -> &'_ str {
         &self.source
     }
+    
+    pub fn span(&self) -> &Span {
+        &self.bounds
+    }
 }
 
 impl<S> Deref for Node<S> {
@@ -54,25 +77,153 @@ impl<S> DerefMut for Node<S> {
     }
 }
 
+/// Cursor over a source string.
+/// 
+/// The position of the parser is the byte end of its [Span], so moving, slicing and rewinding 
+/// never have to walk the source.
+/// 
+/// Parsers created with [Self::derive] share the strings and the state of parsing of the parser 
+/// they were derived from. Clones share only the strings and copy the state of parsing, so that 
+/// clones can parse at the same time, such as on different threads, while a string internalized 
+/// by one clone has the same [Symbol] for all of them. Parsers that should not share strings are 
+/// created with [Self::detach_strings].
 #[derive(Debug)]
-pub struct Parser<'a, Token> {
-    chars: Rc<RefCell<Peekable<Chars<'a>>>>,
+pub struct Parser<Token> {
     source: ByteString,
     span: Span,
+    /// Strings internalized before parsing, which every parser derived from this parser shares.
+    keywords: KeywordTable<Token>,
+    /// Strings internalized while parsing, shared by every parser derived or cloned from the same 
+    /// parser.
     strings: Strings<Token>,
+    /// Tells apart the strings internalized by this parser and the parsers derived from it from 
+    /// those of its clones, so rewinding never removes the strings of a clone.
+    owner: usize,
+    /// Scopes of strings whose tokens take precedence, which parsers derived from this parser 
+    /// start out with and every [ParserString] it creates keeps.
+    scopes: Scopes<Token>,
+    /// The syntax error that occurred furthest into the source, shared by every parser derived 
+    /// from the same parser.
+    furthest: Shared<Lock<Option<SyntaxError<()>>>>,
+    /// Non-fatal diagnostics reported while parsing, shared by every parser derived from the same 
+    /// parser.
+    diagnostics: Shared<Lock<Vec<Diagnostic>>>,
+    /// Memoized results of parsing, shared by every parser derived from the same parser.
+    memo: Shared<Lock<Memo>>,
+    limits: Limits,
+    /// How many nodes were parsed, shared by every parser derived from the same parser.
+    steps: Shared<Lock<usize>>,
+    cancellation: Option<Cancellation>
 }
 
-impl<Token> Clone for Parser<'_, Token> {
+impl<Token> Clone for Parser<Token> {
     fn clone(&self) -> Self {
         Self {
-            chars: Rc::new(RefCell::clone(&self.chars)),
             source: self.source.clone(),
             span: self.span,
-            strings: self.strings.clone()
+            keywords: self.keywords.clone(),
+            strings: self.strings.clone(),
+            owner: next_owner(),
+            scopes: self.scopes.clone(),
+            furthest: Shared::new((*self.furthest).clone()),
+            diagnostics: Shared::new((*self.diagnostics).clone()),
+            memo: Shared::new(Lock::new(self.memo.borrow().detach())),
+            limits: self.limits,
+            steps: Shared::new((*self.steps).clone()),
+            cancellation: self.cancellation.as_ref().map(Cancellation::detach)
+        }
+    }
+}
+
+/// A new owner of internalized strings, which is never the owner of another parser.
+fn next_owner() -> usize {
+    static OWNERS: AtomicUsize = AtomicUsize::new(0);
+    OWNERS.fetch_add(1, Ordering::Relaxed)
+}
+
+/// How often [Parser::parse_while] checks whether parsing was cancelled, in characters.
+const CANCELLATION_INTERVAL: usize = 4096;
+
+/// Check for whether parsing was cancelled, shared by every parser derived from the same parser.
+#[derive(Clone)]
+struct Cancellation {
+    check: Shared<CancellationCheck>,
+    /// Where the parser was when it found out it was cancelled, which stays set from then on.
+    at: Shared<Lock<Option<Span>>>
+}
+
+impl Cancellation {
+    /// Check for a parser that parses apart from this one, which is cancelled by the same 
+    /// function but finds out on its own.
+    fn detach(&self) -> Self {
+        Self {
+            check: self.check.clone(),
+            at: Shared::new((*self.at).clone())
+        }
+    }
+}
+
+impl Debug for Cancellation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cancellation").field("at", &self.at.get()).finish_non_exhaustive()
+    }
+}
+
+/// A saved position of a [Parser] which can be returned to with [Parser::rewind].
+/// 
+/// # Usage
+/// Used to backtrack after trying an alternative that failed to parse, so the characters it 
+/// consumed become available to the next alternative, the strings it internalized and the 
+/// diagnostics it reported are forgotten, and the scopes it entered or left are undone.
+#[derive(Debug, PartialEq)]
+pub struct Checkpoint<Token> {
+    span: Span,
+    /// Generation of the first string internalized after the checkpoint.
+    strings: u32,
+    diagnostics: usize,
+    scopes: Scopes<Token>
+}
+
+impl<Token> Clone for Checkpoint<Token> {
+    fn clone(&self) -> Self {
+        Self {
+            span: self.span,
+            strings: self.strings,
+            diagnostics: self.diagnostics,
+            scopes: self.scopes.clone()
         }
     }
 }
 
+/// Limits on how much work a [Parser] does, so that untrusted input cannot overflow the stack or 
+/// take unbounded time.
+/// 
+/// # Usage
+/// Every limit is unset by default. Parsing fails with [Error::LimitExceeded] as soon as a node 
+/// would exceed a limit.
+/// 
+/// Limits count nodes, including each alternative tried by [Parser::parse_first_of], and not 
+/// characters. Nodes that consume characters without parsing nodes inside of them do work in 
+/// proportion to what they consume, so limiting the length of the source along with the steps 
+/// bounds the time taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    /// Maximum number of nodes being parsed inside of each other.
+    pub depth: Option<usize>,
+    /// Maximum number of nodes parsed in total, including nodes that failed to parse or were 
+    /// backtracked out of. Every node is one step however many characters it consumes.
+    pub steps: Option<usize>
+}
+
+/// Type telling apart the alternatives of [Parser::parse_first_of] from other nodes being parsed.
+struct FirstOf;
+
+/// A function that parses one of the alternatives given to [Parser::parse_first_of].
+pub type Alternative<Token, Data, Type, SpecificError> = fn(&mut Parser<Token>, &mut Data) -> Result<Type, Error<SpecificError>>;
+
+/// Result of parsing a node along with the diagnostics reported while parsing it.
+pub type WithDiagnostics<Type, SpecificError> = (Result<Node<Type>, Error<SpecificError>>, Vec<Diagnostic>);
+
 #[derive(Debug, Error, PartialEq)]
 pub enum ExpectError {
     #[error("Resizing of a blocked span in a parser")]
@@ -88,89 +239,663 @@ pub enum InternalizeError {
     
 }
 
-impl<'a, Token> Parser<'a, Token> {
+impl<Token> Parser<Token> {
+    /// Create a parser at the end of this parser's span, sharing its strings and state of 
+    /// parsing.
     pub fn derive(&self) -> Result<Self, ArithmeticOverflow> {
         Ok(Self {
-            source: self.source.clone(),
-            chars: self.chars.clone(),
             span: self.span.at_end()?,
-            strings: self.strings.clone()
+            ..self.fork()
         })
     }
     
-    pub fn new(source: &'a str) -> Self {
+    /// Create a parser with the same span, sharing the strings and state of parsing of this 
+    /// parser.
+    fn fork(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            span: self.span,
+            keywords: self.keywords.clone(),
+            strings: self.strings.clone(),
+            owner: self.owner,
+            scopes: self.scopes.clone(),
+            furthest: self.furthest.clone(),
+            diagnostics: self.diagnostics.clone(),
+            memo: self.memo.clone(),
+            limits: self.limits,
+            steps: self.steps.clone(),
+            cancellation: self.cancellation.clone()
+        }
+    }
+    
+    /// Set the limits of this parser and every parser derived from it.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+    
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+    
+    /// How many nodes were parsed so far, which is what [Limits::steps] limits.
+    pub fn steps(&self) -> usize {
+        self.steps.get()
+    }
+    
+    /// Cancel parsing once a flag is set, such as by another thread handling the request being 
+    /// parsed.
+    pub fn with_cancellation(self, flag: Arc<AtomicBool>) -> Self {
+        self.with_cancellation_check(move || flag.load(Ordering::Relaxed))
+    }
+    
+    /// Cancel parsing once a function returns true.
+    /// 
+    /// # Usage
+    /// The function is called before parsing every node and periodically while consuming long 
+    /// runs of characters, so it should be cheap. Once parsing is cancelled, every parse fails 
+    /// with [Error::Cancelled] along with where the parser was when it found out.
+    pub fn with_cancellation_check(mut self, check: impl Fn() -> bool + SendSync + 'static) -> Self {
+        self.cancellation = Some(Cancellation {
+            check: Shared::new(check),
+            at: Shared::new(Lock::new(None))
+        });
+        self
+    }
+    
+    /// Whether parsing was cancelled, remembering where the parser was if this is the first time 
+    /// it found out.
+    pub fn is_cancelled(&self) -> bool {
+        let Some(cancellation) = &self.cancellation else { return false };
+        if cancellation.at.get().is_some() { return true }
+        
+        let cancelled = (cancellation.check)();
+        if cancelled { cancellation.at.set(Some(self.span.at_end().unwrap_or(self.span))) }
+        cancelled
+    }
+    
+    /// The error to return if parsing was cancelled.
+    fn cancelled<SpecificError>(&self) -> Option<Error<SpecificError>> {
+        let span = self.cancellation.as_ref()?.at.get()?;
+        Some(Error::Cancelled(Cancelled { span }))
+    }
+    
+    /// Create a parser at the start of the source.
+    /// 
+    /// # Usage
+    /// Passing an owned [ByteString] or [String] avoids copying large sources.
+    pub fn new(source: impl Into<ByteString>) -> Self {
+        Self::with_keywords(source, KeywordTable::default())
+    }
+    
+    /// Create a parser at the start of the source, with the keywords of a table internalized.
+    /// 
+    /// # Usage
+    /// The table is shared rather than copied, so creating many parsers from one table is cheap. 
+    /// Strings internalized while parsing are kept apart from the table, in strings that are only 
+    /// shared by the parsers derived or cloned from this one.
+    pub fn with_keywords(source: impl Into<ByteString>, keywords: KeywordTable<Token>) -> Self {
         Self {
             source: source.into(),
-            chars: Rc::new(RefCell::new(source.chars().peekable())),
             span: Span::default(),
-            strings: Rc::new(RefCell::new(IndexMap::new()))
+            keywords,
+            strings: Shared::new(Lock::default()),
+            owner: next_owner(),
+            scopes: Shared::from([]),
+            furthest: Shared::new(Lock::new(None)),
+            diagnostics: Shared::new(Lock::new(Vec::new())),
+            memo: Shared::new(Lock::new(Memo::default())),
+            limits: Limits::default(),
+            steps: Shared::new(Lock::new(0)),
+            cancellation: None
         }
     }
+    
+    /// Byte index in the source of the next character to be parsed.
+    pub fn position(&self) -> usize {
+        self.span.byte_end()
+    }
+    
+    /// The entire source string this parser reads from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    
+    /// Index the lines of the source to resolve spans into lines and columns.
+    pub fn source_map(&self) -> SourceMap {
+        SourceMap::new(self.source.clone())
+    }
+    
+    /// The part of the source that has not been parsed yet.
+    /// 
+    /// # Usage
+    /// Used for lookahead of any distance without consuming anything.
+    pub fn remaining(&self) -> &str {
+        &self.source[self.position()..]
+    }
+    
+    /// Whether the whole source was consumed.
+    pub fn is_eof(&self) -> bool {
+        self.remaining().is_empty()
+    }
+    
+    /// Expect the whole source to have been consumed.
+    /// 
+    /// # Error
+    /// A [ErrorKind::TrailingInput] syntax error spanning the input that was left over, which 
+    /// found the first character of it.
+    pub fn expect_eof<SpecificError>(&self) -> Result<(), Error<SpecificError>> {
+        if self.is_eof() { return Ok(()) }
+        
+        let mut span = self.span.at_end().map_err(Error::ArithmeticOverflow)?;
+        for character in self.remaining().chars() {
+            span.overflowing_expand(character);
+        }
+        
+        let error = SyntaxError::new(ErrorKind::TrailingInput, span)
+            .with_found(self.peek().map_or(Found::EndOfInput, Found::Char))
+            .with_expected([Expected::EndOfInput]);
+        Err(Error::SyntaxError(error))
+    }
+    
+    /// The next character to be parsed without consuming it.
+    pub fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
 
     pub fn expect_char(&mut self, char: char) -> Result<(), ExpectError> {
-        let mut chars = self.chars.borrow_mut();
-        let peeked = *chars.peek().ok_or(ExpectError::Unexpected)?;
+        let peeked = self.peek().ok_or(ExpectError::Unexpected)?;
         let true = char == peeked else { return Err(ExpectError::Unexpected) };
 
         self.span.overflowing_expand(peeked);
-        chars.next();
-        
         Ok(())
     }
     
+    /// Consume characters for as long as they satisfy the predicate.
+    /// 
+    /// # Usage
+    /// Stops early if parsing was cancelled, which the node being parsed then fails with.
     pub fn parse_while(&mut self, mut predicate: impl FnMut(char) -> bool) -> ParserString<Token> {
-        let mut chars = self.chars.borrow_mut();
         let mut slice_bounds = {
             let byte_end = self.span.byte_end();
             byte_end..byte_end
         };
         
-        loop {
-            let Some(peeked) = chars.peek() else { break };
-            let peeked = *peeked;
-            
+        let mut consumed = 0;
+        while let Some(peeked) = self.peek() {
             if !predicate(peeked) { break }
-            let _ = chars.next();
-        
+            
             self.span.overflowing_expand(peeked);
             slice_bounds.end += peeked.len_utf8();
+            
+            consumed += 1;
+            // The parse this is part of fails with the cancellation once it returns.
+            if consumed % CANCELLATION_INTERVAL == 0 && self.is_cancelled() { break }
         }
         
-        ParserString {
-            strings: self.strings.clone(),
-            // FIXME: Bytestring not being used correctly
-            slice: self.derive_source(slice_bounds),
-            index: None
-        }
+        self.string(slice_bounds)
     }
     
     pub fn parse_till_char(&mut self, char: char) -> ParserString<Token> {
         self.parse_while(|peeked| peeked != char)
     }
     
-    pub fn internalize(&mut self, slice: &str, token: Token) -> Result<ParserString<Token>, InternalizeError> {
-        let mut borrow = self.strings.borrow_mut();
-        let Entry::Vacant(entry) = borrow.entry(slice.into()) else { return Err(InternalizeError::EntryExists) };
-        let index = entry.index();
-        entry.insert(token);
+    /// Consume a string if the remaining source starts with it.
+    /// 
+    /// # Error
+    /// Nothing is consumed if the string does not match.
+    pub fn expect_str(&mut self, string: &str) -> Result<ParserString<Token>, ExpectError> {
+        self.expect_any_str(&[string])
+    }
+    
+    /// Consume the longest of the strings that the remaining source starts with.
+    /// 
+    /// # Error
+    /// Nothing is consumed if none of the strings match.
+    pub fn expect_any_str(&mut self, strings: &[&str]) -> Result<ParserString<Token>, ExpectError> {
+        self.expect_longest(strings, false)
+    }
+    
+    /// Same as [Self::expect_str] but characters are compared without case.
+    pub fn expect_str_ignore_case(&mut self, string: &str) -> Result<ParserString<Token>, ExpectError> {
+        self.expect_any_str_ignore_case(&[string])
+    }
+    
+    /// Same as [Self::expect_any_str] but characters are compared without case.
+    /// 
+    /// # Usage
+    /// The returned string is the text from the source, which can differ in case from the string 
+    /// that matched it.
+    pub fn expect_any_str_ignore_case(&mut self, strings: &[&str]) -> Result<ParserString<Token>, ExpectError> {
+        self.expect_longest(strings, true)
+    }
+    
+    fn expect_longest(&mut self, strings: &[&str], ignore_case: bool) -> Result<ParserString<Token>, ExpectError> {
+        let byte_length = strings
+            .iter()
+            .filter_map(|string| self.match_prefix(string, ignore_case))
+            .max()
+            .ok_or(ExpectError::Unexpected)?;
         
-        Ok(ParserString {
+        Ok(self.consume(byte_length))
+    }
+    
+    /// Find how many bytes of the remaining source match a string.
+    fn match_prefix(&self, string: &str, ignore_case: bool) -> Option<usize> {
+        if !ignore_case {
+            return self.remaining().starts_with(string).then_some(string.len());
+        }
+        
+        let mut remaining = self.remaining().chars();
+        let mut byte_length = 0;
+        
+        for expected in string.chars() {
+            let found = remaining.next()?;
+            if found != expected && !found.to_lowercase().eq(expected.to_lowercase()) { return None }
+            byte_length += found.len_utf8();
+        }
+        
+        Some(byte_length)
+    }
+    
+    /// Consume the next bytes of the remaining source as a string.
+    fn consume(&mut self, byte_length: usize) -> ParserString<Token> {
+        let start = self.position();
+        let range = start..start + byte_length;
+        
+        for character in self.source[range.clone()].chars() {
+            self.span.overflowing_expand(character);
+        }
+        
+        self.string(range)
+    }
+    
+    fn string(&self, range: Range<usize>) -> ParserString<Token> {
+        ParserString {
+            keywords: self.keywords.clone(),
             strings: self.strings.clone(),
-            index: Some(index),
-            slice: slice.into()
-        })
+            owner: self.owner,
+            scopes: self.scopes.clone(),
+            // FIXME: Bytestring not being used correctly
+            slice: self.derive_source(range),
+            symbol: None
+        }
+    }
+    
+    /// Internalize a string along with its token, such as a keyword before parsing.
+    /// 
+    /// # Error
+    /// Fails if the string was already internalized.
+    pub fn internalize(&mut self, slice: &str, token: Token) -> Result<Symbol, InternalizeError> {
+        if self.interner().symbol(slice).is_some() { return Err(InternalizeError::EntryExists) }
+        
+        let (index, generation) = self.strings.borrow_mut().push(slice.into(), token, self.owner);
+        Ok(Symbol::new(self.keywords.len() + index, generation))
+    }
+    
+    /// Enter a scope whose strings take precedence over every string outside of it when finding 
+    /// the token of a string, such as for keywords that only exist inside of attributes.
+    /// 
+    /// # Usage
+    /// The scope applies to this parser and the parsers derived from it until it is left with 
+    /// [Self::pop_scope]. Every [ParserString] created inside of the scope keeps finding its token 
+    /// in it after it was left, so the strings of a node parsed in a scope keep the tokens of the 
+    /// scope. Scopes only change which token a string has, so the [Symbol] of a string stays the 
+    /// same.
+    pub fn push_scope(&mut self, scope: KeywordTable<Token>) {
+        let mut scopes = self.scopes.to_vec();
+        scopes.push(scope);
+        self.scopes = scopes.into();
+    }
+    
+    /// Leave the innermost scope, returning it.
+    pub fn pop_scope(&mut self) -> Option<KeywordTable<Token>> {
+        let (scope, outer) = self.scopes.split_last()?;
+        let scope = scope.clone();
+        self.scopes = Shared::from(outer);
+        Some(scope)
+    }
+    
+    /// Parse a node inside of a scope, leaving it again whether or not the node parsed.
+    pub fn parse_scoped<Type: Parsable<Token=Token> + 'static>(&mut self, scope: KeywordTable<Token>, data: &mut Type::Data) -> Result<Node<Type>, Error<Type::Error>> {
+        self.push_scope(scope);
+        let result = self.parse::<Type>(data);
+        self.pop_scope();
+        result
+    }
+    
+    /// Freeze the keywords and every string internalized so far into a table, which new parsers 
+    /// can be created from with [Self::with_keywords].
+    pub fn freeze_strings(&self) -> KeywordTable<Token> where Token: Clone {
+        let strings = self.strings.borrow();
+        self.keywords
+            .iter()
+            .map(|(keyword, token)| (ByteString::from(keyword), token.clone()))
+            .chain(strings.iter().map(|(string, token)| (string.clone(), token.clone())))
+            .collect()
+    }
+    
+    /// Start a transaction on the internalized strings, which are shared by every parser and 
+    /// [ParserString] derived from this parser.
+    /// 
+    /// # Usage
+    /// Strings internalized after the transaction began are removed again unless it is 
+    /// committed. Transactions can be nested, where rolling back a transaction also removes what 
+    /// was internalized in the transactions inside of it.
+    pub fn begin_transaction(&self) -> Transaction<Token> {
+        Transaction::new(&self.strings, self.owner)
+    }
+    
+    /// Same as [Self::parse], but also removing the strings internalized by the node if it 
+    /// fails to parse.
+    pub fn cloning_parse<Type: Parsable<Token=Token> + 'static>(&mut self, data: &mut Type::Data) -> Result<Node<Type>, Error<Type::Error>> {
+        let transaction = self.begin_transaction();
+        let result = self.parse::<Type>(data);
+        if result.is_ok() { transaction.commit() }
+        result
+    }
+    
+    /// View of the internalized strings, used to resolve [Symbol]s.
+    pub fn interner(&self) -> Interner<'_, Token> {
+        Interner::new(&self.keywords, &self.strings, &self.scopes)
     }
     
     fn derive_source(&self, range: Range<usize>) -> ByteString {
         unsafe { ByteString::from_bytes_unchecked(self.source.clone().into_bytes().slice(range)) }
     }
     
+    /// Save the current position of the parser.
+    /// 
+    /// # Usage
+    /// The checkpoint can be passed to [Self::rewind] to un-consume everything parsed after it.
+    pub fn checkpoint(&self) -> Checkpoint<Token> {
+        Checkpoint { 
+            span: self.span,
+            strings: self.strings.borrow().generation(),
+            diagnostics: self.diagnostics.borrow().len(),
+            scopes: self.scopes.clone()
+        }
+    }
+    
+    /// Return the parser to a position saved with [Self::checkpoint].
+    /// 
+    /// # Usage
+    /// Strings internalized since the checkpoint are removed from the strings shared by this 
+    /// parser, so they are removed for every parser and [ParserString] sharing them. Strings 
+    /// internalized by a clone in the meantime are kept, along with the strings internalized 
+    /// before them. Diagnostics reported since the checkpoint are removed as well, and the parser 
+    /// is in the scopes it was in at the checkpoint again.
+    pub fn rewind(&mut self, checkpoint: Checkpoint<Token>) {
+        self.span = checkpoint.span;
+        self.strings.borrow_mut().truncate(checkpoint.strings, self.owner);
+        self.diagnostics.borrow_mut().truncate(checkpoint.diagnostics);
+        self.scopes = checkpoint.scopes;
+    }
+    
+    /// Report a diagnostic that does not stop parsing, such as a warning or an error that was 
+    /// recovered from.
+    pub fn report(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.borrow_mut().push(diagnostic);
+    }
+    
+    /// Report a syntax error that was recovered from.
+    pub fn report_error<SpecificError: Display>(&mut self, error: &SyntaxError<SpecificError>) {
+        self.report(Diagnostic::from(error));
+    }
+    
+    /// Remove and return every diagnostic reported so far.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        self.diagnostics.take()
+    }
+    
+    /// Parse a node from the current position.
+    /// 
+    /// # Error
+    /// If the node fails to parse, the parser is left where it was before this call so another 
+    /// node can be tried in its place. The node is parsed by a derived parser, so nothing it 
+    /// consumed is visible here until it succeeds.
+    /// 
+    /// A node may parse itself again at the same position, such as an expression parsing an 
+    /// operand at a higher precedence. If it would do so forever, parse it with 
+    /// [Self::parse_guarded] to fail instead, or with [Self::parse_left_recursive] to support it.
     pub fn parse<Type: Parsable<Token=Token> + 'static>(&mut self, data: &mut Type::Data) -> Result<Node<Type>, Error<Type::Error>> {
-        let mut fork = self.derive().map_err(Error::ArithmeticOverflow)?;
-        let supplementary = Type::parse(&mut fork, data)?;
+        self.descend((TypeId::of::<Type>(), 0), |parser| parser.parse_node(|fork| Type::parse(fork, data)))
+    }
+    
+    /// Same as [Self::parse] but failing instead of recursing forever when the node is parsed 
+    /// again at a position where it is already being parsed as the same variant.
+    /// 
+    /// # Usage
+    /// The variant tells apart the nodes a type parses depending on its data, such as the rule 
+    /// of a grammar. Nodes parsed with [Self::parse] are variant `0`.
+    /// 
+    /// # Error
+    /// Fails with [Error::LeftRecursion] where the node would have been parsed again.
+    pub fn parse_guarded<Type: Parsable<Token=Token> + 'static>(&mut self, data: &mut Type::Data, variant: usize) -> Result<Node<Type>, Error<Type::Error>> {
+        let kind = (TypeId::of::<Type>(), variant);
+        let position = self.position();
+        
+        if self.memo.borrow().is_active(kind, position) {
+            let span = self.span.at_end().map_err(Error::ArithmeticOverflow)?;
+            return Err(Error::LeftRecursion(LeftRecursion { node: type_name::<Type>(), span }));
+        }
+        
+        self.descend(kind, |parser| parser.parse_node(|fork| Type::parse(fork, data)))
+    }
+    
+    /// Parse a node inside of the nodes being parsed, failing instead if that would exceed the 
+    /// limits.
+    pub(crate) fn descend<Type, SpecificError>(&mut self, kind: NodeKind, parse: impl FnOnce(&mut Self) -> Result<Type, Error<SpecificError>>) -> Result<Type, Error<SpecificError>> {
+        if self.is_cancelled() && let Some(cancelled) = self.cancelled() { return Err(cancelled) }
+        
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        
+        let limit = if self.limits.steps.is_some_and(|maximum| steps > maximum) {
+            Some(Limit::Steps)
+        } else if self.limits.depth.is_some_and(|maximum| self.memo.borrow().depth() >= maximum) {
+            Some(Limit::Depth)
+        } else {
+            None
+        };
+        
+        if let Some(limit) = limit {
+            let span = self.span.at_end().map_err(Error::ArithmeticOverflow)?;
+            return Err(Error::LimitExceeded(LimitExceeded { limit, span }));
+        }
+        
+        let checkpoint = self.checkpoint();
+        self.memo.borrow_mut().enter(kind, self.position());
+        let result = parse(self);
+        self.memo.borrow_mut().exit();
+        
+        // Whatever the node parsed to may be wrong, as it stopped consuming characters once it 
+        // found out it was cancelled.
+        match self.cancelled() {
+            Some(cancelled) => {
+                self.rewind(checkpoint);
+                Err(cancelled)
+            },
+            None => result
+        }
+    }
+    
+    /// Same as [Self::parse] but for a node that can start with itself, such as 
+    /// `expression = expression "+" term | term`.
+    /// 
+    /// # Usage
+    /// The node must be parsed with this method both where it is used and where it refers to 
+    /// itself. Where it refers to itself at the same position, the node parsed so far is used 
+    /// instead of recursing, failing at first. The node is then parsed again as long as that 
+    /// makes it longer, growing it from the left so that it becomes left-associative.
+    pub fn parse_left_recursive<Type>(&mut self, data: &mut Type::Data) -> Result<Node<Type>, Error<Type::Error>> 
+    where 
+        Type: Parsable<Token=Token> + Clone + SendSync + 'static,
+        Type::Error: Clone + SendSync + 'static
+    {
+        let position = self.position();
+        let grown = self.memo.borrow().seed::<Type, Type::Error>(position).cloned();
+        if let Some(grown) = grown {
+            if let Ok(node) = &grown { self.advance(node.bounds) }
+            return grown;
+        }
+        
+        let checkpoint = self.checkpoint();
+        let mut previous = Err(self.unexpected());
+        let mut seed = previous.clone();
+        
+        loop {
+            self.memo.borrow_mut().set_seed(position, seed.clone());
+            let result = self.parse::<Type>(data);
+            self.rewind(checkpoint.clone());
+            
+            let longer = match (&result, &seed) {
+                (Ok(node), Ok(seed)) => node.bounds.byte_length > seed.bounds.byte_length,
+                (Ok(_), Err(_)) => true,
+                (Err(_), _) => false
+            };
+            
+            if !longer { break }
+            previous = seed;
+            seed = result;
+        }
+        
+        // Parse the longest node again so that what it internalized and reported is kept.
+        self.memo.borrow_mut().set_seed(position, previous);
+        let result = self.parse::<Type>(data);
+        self.memo.borrow_mut().remove_seed::<Type>(position);
+        result
+    }
+    
+    /// Same as [Self::parse] but also returning every diagnostic reported so far, which are 
+    /// removed from the parser.
+    /// 
+    /// # Usage
+    /// Diagnostics are returned even if the node fails to parse, so errors that were recovered 
+    /// from before the failure can be shown along with it.
+    pub fn parse_with_diagnostics<Type: Parsable<Token=Token> + 'static>(&mut self, data: &mut Type::Data) -> WithDiagnostics<Type, Type::Error> {
+        let result = self.parse::<Type>(data);
+        (result, self.take_diagnostics())
+    }
+    
+    /// Enable or disable memoizing the results of parsing a type with [Self::parse_memoized].
+    /// 
+    /// # Usage
+    /// Memoization is shared by every parser derived from this parser. Disabling it forgets the 
+    /// results memoized for the type.
+    pub fn set_memoization<Type: Parsable + 'static>(&mut self, enabled: bool) {
+        self.memo.borrow_mut().set_enabled(TypeId::of::<Type>(), enabled);
+    }
+    
+    /// How often memoized results were reused since the parser was created or the memo was 
+    /// cleared.
+    pub fn memo_stats(&self) -> MemoStats {
+        self.memo.borrow().stats()
+    }
+    
+    /// Forget every memoized result and reset the statistics, keeping which types are memoized.
+    pub fn clear_memo(&mut self) {
+        self.memo.borrow_mut().clear();
+    }
+    
+    /// Same as [Self::parse] but reusing the result of parsing the type at the same position 
+    /// before, if memoization was enabled for it with [Self::set_memoization].
+    /// 
+    /// # Usage
+    /// Used for nodes that are parsed at the same position many times while backtracking, which 
+    /// would otherwise take exponential time on some grammars. A reused node consumes what it 
+    /// consumed when it was parsed and reports the diagnostics it reported again.
+    /// 
+    /// Results are keyed only by the type and position, so the type must parse the same way 
+    /// regardless of the data. Strings that were internalized while parsing it are not 
+    /// internalized again when the result is reused. Only nodes and syntax errors are memoized, 
+    /// so errors such as [Error::LeftRecursion] or [Error::Cancelled] are returned again only if 
+    /// parsing fails the same way again.
+    pub fn parse_memoized<Type>(&mut self, data: &mut Type::Data) -> Result<Node<Type>, Error<Type::Error>> 
+    where 
+        Type: Parsable<Token=Token> + Clone + SendSync + 'static,
+        Type::Error: Clone + SendSync + 'static
+    {
+        if !self.memo.borrow().is_enabled(TypeId::of::<Type>()) {
+            return self.parse::<Type>(data);
+        }
+        
+        let position = self.position();
+        let memoized = self.memo
+            .borrow_mut()
+            .get::<Type, Type::Error>(position)
+            .map(|entry| (entry.result.clone(), entry.diagnostics.clone()));
+        
+        if let Some((result, diagnostics)) = memoized {
+            self.diagnostics.borrow_mut().extend(diagnostics);
+            if let Ok(node) = &result { self.advance(node.bounds) }
+            return result;
+        }
+        
+        let reported = self.diagnostics.borrow().len();
+        let result = self.parse::<Type>(data);
+        let diagnostics = self.diagnostics.borrow().get(reported..).unwrap_or_default().to_vec();
         
-        self.span.length += fork.span.length;
-        self.span.byte_length += fork.span.byte_length;
+        // Other errors depend on the nodes being parsed around this one or on state outside of 
+        // the source, so parsing again at the same position may not fail the same way.
+        if matches!(result, Ok(_) | Err(Error::SyntaxError(_))) {
+            self.memo.borrow_mut().insert(position, memo::Entry { result: result.clone(), diagnostics });
+        }
+        result
+    }
+    
+    /// Parse a node that must span the rest of the source.
+    /// 
+    /// # Error
+    /// If input is left over after the node, the error of [Self::expect_eof] is returned and the 
+    /// parser is left where it was before this call.
+    pub fn parse_complete<Type: Parsable<Token=Token> + 'static>(&mut self, data: &mut Type::Data) -> Result<Node<Type>, Error<Type::Error>> {
+        let checkpoint = self.checkpoint();
+        let node = self.parse::<Type>(data)?;
+        
+        if let Err(error) = self.expect_eof() {
+            self.rewind(checkpoint);
+            return Err(error);
+        }
+        Ok(node)
+    }
+    
+    /// Parse a node with the first of the alternatives that succeeds, trying them in order.
+    /// 
+    /// # Error
+    /// The parser is rewound between attempts. If every alternative fails, the error lists the 
+    /// error of each alternative in the order they were tried. Errors other than syntax errors 
+    /// are returned as soon as an alternative fails with one. Each alternative counts towards the 
+    /// [Limits] as a node.
+    pub fn parse_first_of<Type, Data, SpecificError>(&mut self, data: &mut Data, alternatives: &[Alternative<Token, Data, Type, SpecificError>]) -> Result<Node<Type>, Error<Vec<Error<SpecificError>>>> {
+        let checkpoint = self.checkpoint();
+        let mut errors = Vec::with_capacity(alternatives.len());
+        
+        for (index, alternative) in alternatives.iter().enumerate() {
+            let kind = (TypeId::of::<FirstOf>(), index);
+            match self.descend(kind, |parser| parser.parse_node(|fork| alternative(fork, data))) {
+                Ok(node) => return Ok(node),
+                Err(error) => match error.fatal() {
+                    Ok(fatal) => return Err(fatal),
+                    Err(error) => errors.push(error)
+                }
+            }
+            
+            self.rewind(checkpoint.clone());
+        }
+        
+        let mut error = SyntaxError::<()>::new(ErrorKind::Unexpected, self.span.at_end().map_err(Error::ArithmeticOverflow)?);
+        for syntax_error in errors.iter().filter_map(Error::syntax) {
+            error.merge_furthest(syntax_error);
+        }
+        
+        Err(Error::SyntaxError(error.with_kind(ErrorKind::Specific(errors))))
+    }
+    
+    fn parse_node<Type, SpecificError>(&mut self, parse: impl FnOnce(&mut Self) -> Result<Type, Error<SpecificError>>) -> Result<Node<Type>, Error<SpecificError>> {
+        let mut fork = self.derive().map_err(Error::ArithmeticOverflow)?;
+        let supplementary = parse(&mut fork).map_err(|error| self.record_failure(error))?;
+        self.advance(fork.span);
         
         Ok(Node {
             bounds: fork.span,
@@ -179,20 +904,94 @@ impl<'a, Token> Parser<'a, Token> {
         })
     }
     
+    /// Consume what a node spans, after it was parsed from the current position.
+    fn advance(&mut self, bounds: Span) {
+        self.span.length += bounds.length;
+        self.span.byte_length += bounds.byte_length;
+    }
+    
+    /// Merge a syntax error with the furthest one seen so far.
+    /// 
+    /// # Usage
+    /// Errors that occurred at the same furthest position get the expected sets of each other, 
+    /// so the error that ends up being reported lists everything that could have been parsed 
+    /// there, including by alternatives that were already backtracked out of.
+    fn record_failure<SpecificError>(&self, error: Error<SpecificError>) -> Error<SpecificError> {
+        let Error::SyntaxError(mut error) = error else { return error };
+        
+        let mut furthest = self.furthest.borrow_mut();
+        let furthest = furthest.get_or_insert_with(|| SyntaxError::new(ErrorKind::Unexpected, *error.span()));
+        furthest.merge_furthest(&error);
+        
+        if furthest.span().byte_start == error.span().byte_start {
+            error.merge_furthest(furthest);
+        }
+        
+        Error::SyntaxError(error)
+    }
+    
+    /// Same as cloning the parser but copying the strings internalized so far, so that strings 
+    /// internalized by either parser from then on are not seen by the other.
+    pub fn detach_strings(&self) -> Self {
+        Self {
+            strings: Shared::new((*self.strings).clone()),
+            ..self.clone()
+        }
+    }
+    
+    /// Create a parser at the same position which does not share the record of the furthest 
+    /// syntax error.
+    /// 
+    /// # Usage
+    /// Used when a failure to parse is the desired outcome, such as in a negative lookahead, so 
+    /// that what it tried does not show up as expected in other errors.
+    pub fn detach_failures(&self) -> Self {
+        Self {
+            furthest: Shared::new(Lock::new(None)),
+            ..self.fork()
+        }
+    }
+    
+    /// Syntax error at the next character, recording what was found there.
+    pub fn unexpected<SpecificError>(&self) -> Error<SpecificError> {
+        self.syntax_error(ErrorKind::Unexpected)
+    }
+    
+    /// Same as [Self::unexpected] but also recording what was expected at the next character.
+    pub fn expected<SpecificError>(&self, expected: impl IntoIterator<Item = Expected>) -> Error<SpecificError> {
+        match self.unexpected() {
+            Error::SyntaxError(error) => Error::SyntaxError(error.with_expected(expected)),
+            error => error
+        }
+    }
+    
+    /// Syntax error with a specific error at the next character.
+    pub fn specific_error<SpecificError>(&self, error: SpecificError) -> Error<SpecificError> {
+        self.syntax_error(ErrorKind::Specific(error))
+    }
+    
+    fn syntax_error<SpecificError>(&self, kind: ErrorKind<SpecificError>) -> Error<SpecificError> {
+        let mut span = match self.span.at_end() {
+            Ok(span) => span,
+            Err(overflow) => return Error::ArithmeticOverflow(overflow)
+        };
+        
+        let found = match self.peek() {
+            Some(peeked) => {
+                span.overflowing_expand(peeked);
+                Found::Char(peeked)
+            },
+            None => Found::EndOfInput
+        };
+        
+        Error::SyntaxError(SyntaxError::new(kind, span).with_found(found))
+    }
+    
     pub fn span(&self) -> &Span {
         &self.span
     }
 }
 
-impl<Token: Clone> Parser<'_, Token> {
-    pub fn cloning_parse<Type: Parsable<Token=Token> + 'static>(&mut self, data: &mut Type::Data) -> Result<Node<Type>, Error<Type::Error>> {
-        let strings = Rc::new(RefCell::clone(&self.strings));
-        self.parse::<Type>(data).inspect_err(move |_| {
-            self.strings = strings;
-        })
-    }
-}
-
 pub trait Parsable: Sized {
     type Error;
     type Token;
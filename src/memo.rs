@@ -0,0 +1,135 @@
+use core::any::TypeId;
+use std::collections::{HashMap, HashSet};
+use crate::Node;
+use crate::diagnostic::Diagnostic;
+use crate::error::Error;
+use crate::sync::{AnyBox, SendSync};
+
+#[cfg(test)]
+mod test;
+
+/// Statistics of how often memoized results were reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoStats {
+    /// Parses that returned a memoized result.
+    pub hits: usize,
+    /// Parses of a memoized type that had to parse because there was no result yet.
+    pub misses: usize
+}
+
+/// Type of a node, along with a number telling apart nodes of the same type that parse
+/// differently depending on their data.
+pub(crate) type NodeKind = (TypeId, usize);
+
+/// Results of parsing types at positions of the source, shared by every parser derived from the
+/// same parser.
+#[derive(Debug, Default)]
+pub(crate) struct Memo {
+    /// Types whose results are memoized.
+    types: HashSet<TypeId>,
+    /// Result of parsing a type at a byte index, each being an [Entry] of that type.
+    entries: HashMap<(TypeId, usize), AnyBox>,
+    stats: MemoStats,
+    /// Kinds of nodes being parsed and where they started, innermost last.
+    active: Vec<(NodeKind, usize)>,
+    /// Results grown so far for left-recursive types being parsed, each being a result of that 
+    /// type.
+    seeds: HashMap<(TypeId, usize), AnyBox>
+}
+
+/// Memoized result of parsing a type.
+pub(crate) struct Entry<Type, SpecificError> {
+    pub result: Result<Node<Type>, Error<SpecificError>>,
+    /// Diagnostics reported while parsing, reported again every time the result is reused.
+    pub diagnostics: Vec<Diagnostic>
+}
+
+impl Memo {
+    pub fn set_enabled(&mut self, type_id: TypeId, enabled: bool) {
+        if enabled {
+            self.types.insert(type_id);
+        } else {
+            self.types.remove(&type_id);
+            self.entries.retain(|(entry_type, _), _| *entry_type != type_id);
+        }
+    }
+
+    pub fn is_enabled(&self, type_id: TypeId) -> bool {
+        self.types.contains(&type_id)
+    }
+
+    /// Memo for a parser that parses apart from this one, memoizing the same types but none of 
+    /// the results.
+    pub fn detach(&self) -> Self {
+        Self {
+            types: self.types.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// The memoized result of a type at a byte index, counting it as a hit or a miss.
+    pub fn get<Type: 'static, SpecificError: 'static>(&mut self, position: usize) -> Option<&Entry<Type, SpecificError>> {
+        let entry = self.entries
+            .get(&(TypeId::of::<Type>(), position))
+            .and_then(|entry| entry.downcast_ref::<Entry<Type, SpecificError>>());
+
+        match entry {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1
+        }
+        entry
+    }
+
+    pub fn insert<Type: SendSync + 'static, SpecificError: SendSync + 'static>(&mut self, position: usize, entry: Entry<Type, SpecificError>) {
+        self.entries.insert((TypeId::of::<Type>(), position), Box::new(entry));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.stats = MemoStats::default();
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+
+    /// Whether a kind of node is already being parsed at a byte index, meaning it is 
+    /// left-recursive.
+    pub fn is_active(&self, kind: NodeKind, position: usize) -> bool {
+        // Nodes are parsed from where their parent is, so only the innermost nodes can have
+        // started at the same position.
+        self.active
+            .iter()
+            .rev()
+            .take_while(|(_, start)| *start == position)
+            .any(|(active, _)| *active == kind)
+    }
+
+    /// How many nodes are being parsed inside of each other.
+    pub fn depth(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn enter(&mut self, kind: NodeKind, position: usize) {
+        self.active.push((kind, position));
+    }
+
+    pub fn exit(&mut self) {
+        self.active.pop();
+    }
+
+    /// The result grown so far for a left-recursive type at a byte index, if it is being grown.
+    pub fn seed<Type: 'static, SpecificError: 'static>(&self, position: usize) -> Option<&Result<Node<Type>, Error<SpecificError>>> {
+        self.seeds
+            .get(&(TypeId::of::<Type>(), position))
+            .and_then(|seed| seed.downcast_ref())
+    }
+
+    pub fn set_seed<Type: SendSync + 'static, SpecificError: SendSync + 'static>(&mut self, position: usize, seed: Result<Node<Type>, Error<SpecificError>>) {
+        self.seeds.insert((TypeId::of::<Type>(), position), Box::new(seed));
+    }
+
+    pub fn remove_seed<Type: 'static>(&mut self, position: usize) {
+        self.seeds.remove(&(TypeId::of::<Type>(), position));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{Limits, Parsable, Parser};
+use crate::diagnostic::Diagnostic;
+use crate::error::{Error, Expected};
+use super::MemoStats;
+
+/// Parses a word, counting how many times it was parsed.
+#[derive(Debug, Clone)]
+struct Word;
+
+impl Parsable for Word {
+    type Error = ();
+    type Token = ();
+    type Data = usize;
+
+    fn parse(parser: &mut Parser<Self::Token>, parses: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        *parses += 1;
+        if parser.parse_while(char::is_alphabetic).is_empty() {
+            return Err(parser.expected([Expected::Node("word".into())]));
+        }
+        if parser.expect_char('~').is_ok() {
+            parser.report(Diagnostic::warning("tilde"));
+        }
+        Ok(Self)
+    }
+}
+
+/// Parses a word and then a character, so that both alternatives parse the word at the same 
+/// position.
+fn word_then(parser: &mut Parser<()>, parses: &mut usize, character: char) -> Result<(), Error<()>> {
+    parser.parse_memoized::<Word>(parses)?;
+    parser.expect_char(character).map_err(|_| parser.expected([Expected::Char(character)]))
+}
+
+#[test]
+fn memoize() {
+    let mut parser = Parser::<()>::new("word?");
+    parser.set_memoization::<Word>(true);
+    let mut parses = 0;
+
+    let checkpoint = parser.checkpoint();
+    assert!(word_then(&mut parser, &mut parses, '!').is_err());
+    parser.rewind(checkpoint);
+    assert!(word_then(&mut parser, &mut parses, '?').is_ok());
+
+    assert_eq!(parses, 1);
+    assert_eq!(parser.position(), 5);
+    assert_eq!(parser.memo_stats(), MemoStats { hits: 1, misses: 1 });
+
+    parser.clear_memo();
+    assert_eq!(parser.memo_stats(), MemoStats::default());
+}
+
+#[test]
+fn memoize_errors_and_diagnostics() {
+    let mut parser = Parser::<()>::new("word~ 1");
+    parser.set_memoization::<Word>(true);
+    let mut parses = 0;
+
+    for _ in 0..2 {
+        let checkpoint = parser.checkpoint();
+        parser.parse_memoized::<Word>(&mut parses).unwrap();
+        assert_eq!(parser.take_diagnostics().len(), 1);
+        parser.rewind(checkpoint);
+    }
+
+    parser.expect_str("word~ ").unwrap();
+    for _ in 0..2 {
+        let error = parser.parse_memoized::<Word>(&mut parses).unwrap_err();
+        assert_eq!(error.syntax().unwrap().span().byte_start, 6);
+    }
+
+    assert_eq!(parses, 2);
+    assert_eq!(parser.memo_stats(), MemoStats { hits: 2, misses: 2 });
+}
+
+/// Parses a memoized word inside of another node.
+#[derive(Debug)]
+struct Nested;
+
+impl Parsable for Nested {
+    type Error = ();
+    type Token = ();
+    type Data = usize;
+
+    fn parse(parser: &mut Parser<Self::Token>, parses: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        parser.parse_memoized::<Word>(parses)?;
+        Ok(Self)
+    }
+}
+
+#[test]
+fn limits_not_memoized() {
+    let mut parser = Parser::<()>::new("word").with_limits(Limits { depth: Some(1), steps: None });
+    parser.set_memoization::<Word>(true);
+    let mut parses = 0;
+
+    assert!(matches!(parser.parse::<Nested>(&mut parses), Err(Error::LimitExceeded(_))));
+    assert!(parser.parse_memoized::<Word>(&mut parses).is_ok());
+    assert_eq!(parses, 1);
+    assert_eq!(parser.memo_stats(), MemoStats { hits: 0, misses: 2 });
+}
+
+#[test]
+fn disabled() {
+    let mut parser = Parser::<()>::new("word");
+    let mut parses = 0;
+
+    for _ in 0..2 {
+        let checkpoint = parser.checkpoint();
+        parser.parse_memoized::<Word>(&mut parses).unwrap();
+        parser.rewind(checkpoint);
+    }
+
+    assert_eq!(parses, 2);
+    assert_eq!(parser.memo_stats(), MemoStats::default());
+}
@@ -1,3 +1,4 @@
+use core::marker::PhantomData;
 use crate::{Parsable, Parser};
 use crate::error::Error;
 
@@ -27,4 +28,20 @@ impl Parsable for Line {
         parser.parse_while(|character| character.is_whitespace() && character != '\n');
         Ok(Self)
     }
-}
\ No newline at end of file
+}
+
+/// Same as [Space] but for parsers of any token type, such as in code generated by the derive 
+/// macro.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Whitespace<Token>(PhantomData<Token>);
+
+impl<Token> Parsable for Whitespace<Token> {
+    type Error = ();
+    type Token = Token;
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, _: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        parser.parse_while(|character| character.is_whitespace());
+        Ok(Self(PhantomData))
+    }
+}
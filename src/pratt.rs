@@ -0,0 +1,359 @@
+use core::any::TypeId;
+use bytestring::ByteString;
+use indexmap::IndexMap;
+use crate::{ExpectError, Node, Parsable, Parser, ParserString};
+use crate::error::{Error, Expected};
+use crate::span::Span;
+use crate::string::Symbol;
+
+#[cfg(test)]
+mod test;
+
+/// How operators of the same precedence group when they are chained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`.
+    Right,
+    /// `a == b == c` is a syntax error.
+    None
+}
+
+/// Operator that comes after its first operand, along with the other operators it is written 
+/// with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Trailing<Operator> {
+    Infix(Associativity),
+    Postfix,
+    /// `condition ? then : otherwise`, with the separator between the second and third operand.
+    Ternary { separator: Operator },
+    /// `callee(arguments)`, with the separator between arguments and the closing operator.
+    Call { separator: Operator, close: Operator },
+    /// `operand[index]`, with the closing operator.
+    Index { close: Operator }
+}
+
+/// Table of operators used by [Parser::parse_expression].
+///
+/// # Usage
+/// Operators are registered by the [Symbol] of their internalized string along with a 
+/// precedence, where operators of a higher precedence bind tighter, so the operators of a 
+/// language can come from its keyword table or from runtime data. The symbols are resolved by 
+/// the parser given to [Parser::parse_expression], and operators it cannot resolve are never 
+/// parsed. The tokens of parsed operators can be found with [ParserString::token].
+///
+/// When several operators match, the longest is used. Operators ending in a letter, digit or `_`, 
+/// such as `in`, only match where no such character follows, so they are not found at the start 
+/// of a name like `index`. An operator can be both prefix and
+/// trailing, such as `-`, since prefix operators are only looked for where an operand is
+/// expected.
+#[derive(Debug, Clone, Default)]
+pub struct Operators {
+    prefix: IndexMap<Symbol, u8>,
+    trailing: IndexMap<Symbol, (u8, Trailing<Symbol>)>,
+    /// Whether whitespace is skipped around operators.
+    space: bool
+}
+
+/// Operators whose symbols were resolved to the strings they are written as.
+#[derive(Debug)]
+struct Table {
+    prefix: IndexMap<ByteString, u8>,
+    trailing: IndexMap<ByteString, (u8, Trailing<ByteString>)>,
+    space: bool
+}
+
+/// Expression parsed by [Parser::parse_expression], where every operand spans all of its own
+/// operators and operands.
+#[derive(Debug, Clone)]
+pub enum Expression<Atom: Parsable> {
+    Atom(Node<Atom>),
+    Prefix {
+        operator: ParserString<Atom::Token>,
+        operand: Box<Node<Expression<Atom>>>
+    },
+    Infix {
+        left: Box<Node<Expression<Atom>>>,
+        operator: ParserString<Atom::Token>,
+        right: Box<Node<Expression<Atom>>>
+    },
+    Postfix {
+        operand: Box<Node<Expression<Atom>>>,
+        operator: ParserString<Atom::Token>
+    },
+    Ternary {
+        condition: Box<Node<Expression<Atom>>>,
+        operator: ParserString<Atom::Token>,
+        then: Box<Node<Expression<Atom>>>,
+        separator: ParserString<Atom::Token>,
+        otherwise: Box<Node<Expression<Atom>>>
+    },
+    Call {
+        callee: Box<Node<Expression<Atom>>>,
+        operator: ParserString<Atom::Token>,
+        arguments: Vec<Node<Expression<Atom>>>
+    },
+    Index {
+        operand: Box<Node<Expression<Atom>>>,
+        operator: ParserString<Atom::Token>,
+        index: Box<Node<Expression<Atom>>>
+    }
+}
+
+impl Operators {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skip whitespace before and after every operator.
+    pub fn with_space(mut self) -> Self {
+        self.space = true;
+        self
+    }
+
+    pub fn prefix(mut self, operator: Symbol, precedence: u8) -> Self {
+        self.prefix.insert(operator, precedence);
+        self
+    }
+
+    pub fn infix(mut self, operator: Symbol, precedence: u8, associativity: Associativity) -> Self {
+        self.trailing.insert(operator, (precedence, Trailing::Infix(associativity)));
+        self
+    }
+
+    pub fn postfix(mut self, operator: Symbol, precedence: u8) -> Self {
+        self.trailing.insert(operator, (precedence, Trailing::Postfix));
+        self
+    }
+
+    /// Register a right-associative ternary operator such as `? :`.
+    pub fn ternary(mut self, operator: Symbol, separator: Symbol, precedence: u8) -> Self {
+        self.trailing.insert(operator, (precedence, Trailing::Ternary { separator }));
+        self
+    }
+
+    /// Register a call such as `( , )`, which takes any number of arguments.
+    pub fn call(mut self, open: Symbol, separator: Symbol, close: Symbol, precedence: u8) -> Self {
+        self.trailing.insert(open, (precedence, Trailing::Call { separator, close }));
+        self
+    }
+
+    /// Register an index such as `[ ]`.
+    pub fn index(mut self, open: Symbol, close: Symbol, precedence: u8) -> Self {
+        self.trailing.insert(open, (precedence, Trailing::Index { close }));
+        self
+    }
+
+    /// Resolve the symbols of the operators with a parser, leaving out operators written with a 
+    /// symbol it cannot resolve.
+    fn resolve<Token>(&self, parser: &Parser<Token>) -> Table {
+        let interner = parser.interner();
+        let resolve = |symbol: Symbol| interner.resolve(symbol);
+
+        let prefix = self.prefix
+            .iter()
+            .filter_map(|(operator, precedence)| Some((resolve(*operator)?, *precedence)))
+            .collect();
+        let trailing = self.trailing
+            .iter()
+            .filter_map(|(operator, (precedence, kind))| {
+                let kind = match *kind {
+                    Trailing::Infix(associativity) => Trailing::Infix(associativity),
+                    Trailing::Postfix => Trailing::Postfix,
+                    Trailing::Ternary { separator } => Trailing::Ternary { separator: resolve(separator)? },
+                    Trailing::Call { separator, close } => Trailing::Call { separator: resolve(separator)?, close: resolve(close)? },
+                    Trailing::Index { close } => Trailing::Index { close: resolve(close)? }
+                };
+                Some((resolve(*operator)?, (*precedence, kind)))
+            })
+            .collect();
+
+        Table { prefix, trailing, space: self.space }
+    }
+}
+
+impl Table {
+    fn skip_space<Token>(&self, parser: &mut Parser<Token>) {
+        if self.space {
+            parser.parse_while(char::is_whitespace);
+        }
+    }
+}
+
+/// Binding powers of an operator on its left and right, derived from its precedence so that
+/// operators of a higher precedence always bind tighter.
+fn binding_power(precedence: u8, associativity: Associativity) -> (u16, u16) {
+    let base = u16::from(precedence) * 2 + 2;
+    match associativity {
+        Associativity::Right => (base + 1, base),
+        Associativity::Left | Associativity::None => (base, base + 1)
+    }
+}
+
+impl<Token: 'static> Parser<Token> {
+    /// Parse an expression of atoms and the operators in a table, respecting their precedence and
+    /// associativity.
+    ///
+    /// # Error
+    /// If the expression fails to parse, the parser is rewound to where it was before this call.
+    /// Chaining non-associative operators of the same precedence is a syntax error at the second
+    /// operator.
+    pub fn parse_expression<Atom: Parsable<Token = Token> + 'static>(&mut self, operators: &Operators, data: &mut Atom::Data) -> Result<Node<Expression<Atom>>, Error<Atom::Error>> {
+        let operators = operators.resolve(self);
+        let checkpoint = self.checkpoint();
+        let result = expression(self, &operators, data, 0);
+        if result.is_err() { self.rewind(checkpoint) }
+        result
+    }
+}
+
+/// Parse an expression whose operators bind at least as tightly as a binding power.
+fn expression<Atom: Parsable + 'static>(parser: &mut Parser<Atom::Token>, operators: &Table, data: &mut Atom::Data, minimum: u16) -> Result<Node<Expression<Atom>>, Error<Atom::Error>> {
+    // Operands are parsed without going through `Parser::parse`, so they count towards the limits
+    // here instead.
+    parser.descend((TypeId::of::<Expression<Atom>>(), 0), |parser| operation(parser, operators, data, minimum))
+}
+
+fn operation<Atom: Parsable + 'static>(parser: &mut Parser<Atom::Token>, operators: &Table, data: &mut Atom::Data, minimum: u16) -> Result<Node<Expression<Atom>>, Error<Atom::Error>> {
+    let start = parser.span.at_end().map_err(Error::ArithmeticOverflow)?;
+    let prefixes = operators.prefix.keys().map(|operator| &**operator).collect::<Vec<_>>();
+    let trailing = operators.trailing.keys().map(|operator| &**operator).collect::<Vec<_>>();
+
+    let mut left = match expect_operator(parser, &prefixes) {
+        Ok(operator) => {
+            let (_, right) = binding_power(operators.prefix[&*operator], Associativity::Left);
+            operators.skip_space(parser);
+            let operand = expression(parser, operators, data, right)?;
+            node(parser, start, Expression::Prefix { operator, operand: Box::new(operand) })
+        },
+        Err(_) => {
+            let atom = parser.parse::<Atom>(data)?;
+            Node {
+                bounds: atom.bounds,
+                source: atom.source.clone(),
+                supplementary: Expression::Atom(atom)
+            }
+        }
+    };
+
+    // Precedence of the last non-associative operator, which cannot be chained.
+    let mut non_associative = None;
+
+    loop {
+        let checkpoint = parser.checkpoint();
+        operators.skip_space(parser);
+
+        let Ok(operator) = expect_operator(parser, &trailing) else {
+            parser.rewind(checkpoint);
+            break;
+        };
+        let (precedence, kind) = &operators.trailing[&*operator];
+        let precedence = *precedence;
+        let associativity = match kind {
+            Trailing::Infix(associativity) => *associativity,
+            Trailing::Ternary { .. } => Associativity::Right,
+            _ => Associativity::Left
+        };
+
+        let (binding, right) = binding_power(precedence, associativity);
+        if binding < minimum {
+            parser.rewind(checkpoint);
+            break;
+        }
+
+        if associativity == Associativity::None {
+            if non_associative == Some(precedence) {
+                parser.rewind(checkpoint);
+                operators.skip_space(parser);
+                return Err(parser.unexpected());
+            }
+            non_associative = Some(precedence);
+        }
+
+        let left_node = Box::new(left);
+        if *kind != Trailing::Postfix { operators.skip_space(parser) }
+
+        let supplementary = match kind {
+            Trailing::Infix(_) => {
+                let right = expression(parser, operators, data, right)?;
+                Expression::Infix { left: left_node, operator, right: Box::new(right) }
+            },
+            Trailing::Postfix => Expression::Postfix { operand: left_node, operator },
+            Trailing::Ternary { separator } => {
+                let then = expression(parser, operators, data, 0)?;
+                operators.skip_space(parser);
+                let separator = expect(parser, separator)?;
+                operators.skip_space(parser);
+                let otherwise = expression(parser, operators, data, right)?;
+
+                Expression::Ternary { condition: left_node, operator, then: Box::new(then), separator, otherwise: Box::new(otherwise) }
+            },
+            Trailing::Call { separator, close } => {
+                let mut arguments = Vec::new();
+
+                while expect_operator(parser, &[close]).is_err() {
+                    if !arguments.is_empty() {
+                        expect_operator(parser, &[separator]).map_err(|_| parser.expected([
+                            Expected::String(separator.to_string().into()), 
+                            Expected::String(close.to_string().into())
+                        ]))?;
+                        operators.skip_space(parser);
+                    }
+                    arguments.push(expression(parser, operators, data, 0)?);
+                    operators.skip_space(parser);
+                }
+
+                Expression::Call { callee: left_node, operator, arguments }
+            },
+            Trailing::Index { close } => {
+                let index = expression(parser, operators, data, 0)?;
+                operators.skip_space(parser);
+                expect(parser, close)?;
+
+                Expression::Index { operand: left_node, operator, index: Box::new(index) }
+            }
+        };
+
+        left = node(parser, start, supplementary);
+    }
+
+    Ok(left)
+}
+
+/// Node of an expression from where it started to the current position of the parser.
+fn node<Atom: Parsable>(parser: &Parser<Atom::Token>, start: Span, supplementary: Expression<Atom>) -> Node<Expression<Atom>> {
+    let end = parser.span;
+    let bounds = Span {
+        start: start.start,
+        length: end.start + end.length - start.start,
+        byte_start: start.byte_start,
+        byte_length: end.byte_end() - start.byte_start
+    };
+
+    Node {
+        bounds,
+        source: parser.derive_source(bounds.byte_range()),
+        supplementary
+    }
+}
+
+fn expect<Token, SpecificError>(parser: &mut Parser<Token>, string: &str) -> Result<ParserString<Token>, Error<SpecificError>> {
+    expect_operator(parser, &[string]).map_err(|_| parser.expected([Expected::String(string.to_owned().into())]))
+}
+
+/// Consume the longest of the operators that the remaining source starts with, leaving out 
+/// operators ending in a character of a name that would be followed by another one.
+fn expect_operator<Token>(parser: &mut Parser<Token>, operators: &[&str]) -> Result<ParserString<Token>, ExpectError> {
+    let is_name = |character: char| character.is_alphanumeric() || character == '_';
+    let operators = operators
+        .iter()
+        .copied()
+        .filter(|operator| {
+            let Some(rest) = parser.remaining().strip_prefix(operator) else { return false };
+            !operator.ends_with(is_name) || !rest.starts_with(is_name)
+        })
+        .collect::<Vec<_>>();
+
+    parser.expect_any_str(&operators)
+}
@@ -0,0 +1,132 @@
+use bytestring::ByteString;
+use crate::{Node, Parsable, Parser};
+use crate::error::{Error, Expected};
+use crate::keywords::KeywordTable;
+use super::{Associativity, Expression, Operators};
+
+const OPERATORS: [&str; 13] = ["?", ":", "==", "+", "-", "*", "^", "!", "(", ",", ")", "[", "]"];
+
+/// Number or name, which is internalized.
+#[derive(Debug, Clone)]
+struct Atom;
+
+impl Parsable for Atom {
+    type Error = ();
+    type Token = ();
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, _: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let mut atom = parser.parse_while(char::is_alphanumeric);
+        if atom.is_empty() {
+            return Err(parser.expected([Expected::Node("atom".into())]));
+        }
+        let _ = atom.try_internalize(|_| Some(()));
+        Ok(Self)
+    }
+}
+
+/// Parser whose keywords are the operators.
+fn parser(source: &str) -> Parser<()> {
+    let keywords = OPERATORS.into_iter().map(|operator| (ByteString::from(operator), ())).collect::<KeywordTable<_>>();
+    Parser::with_keywords(source, keywords)
+}
+
+fn operators(parser: &Parser<()>) -> Operators {
+    let symbol = |operator| parser.interner().symbol(operator).unwrap();
+
+    Operators::new()
+        .with_space()
+        .ternary(symbol("?"), symbol(":"), 0)
+        .infix(symbol("=="), 1, Associativity::None)
+        .infix(symbol("+"), 2, Associativity::Left)
+        .infix(symbol("-"), 2, Associativity::Left)
+        .infix(symbol("*"), 3, Associativity::Left)
+        .infix(symbol("^"), 4, Associativity::Right)
+        .prefix(symbol("-"), 5)
+        .postfix(symbol("!"), 6)
+        .call(symbol("("), symbol(","), symbol(")"), 7)
+        .index(symbol("["), symbol("]"), 7)
+}
+
+/// Write an expression as an S-expression.
+fn show(expression: &Node<Expression<Atom>>) -> String {
+    match &**expression {
+        Expression::Atom(atom) => atom.slice().to_owned(),
+        Expression::Prefix { operator, operand } => format!("({} {})", &**operator, show(operand)),
+        Expression::Infix { left, operator, right } => format!("({} {} {})", &**operator, show(left), show(right)),
+        Expression::Postfix { operand, operator } => format!("({} {})", &**operator, show(operand)),
+        Expression::Ternary { condition, then, otherwise, .. } => format!("(? {} {} {})", show(condition), show(then), show(otherwise)),
+        Expression::Call { callee, arguments, .. } => {
+            let arguments = arguments.iter().map(show).collect::<Vec<_>>();
+            format!("(call {} [{}])", show(callee), arguments.join(" "))
+        },
+        Expression::Index { operand, index, .. } => format!("(index {} {})", show(operand), show(index))
+    }
+}
+
+fn parse(source: &str) -> Result<String, Error<()>> {
+    let mut parser = parser(source);
+    parser.parse_expression::<Atom>(&operators(&parser), &mut ()).map(|expression| show(&expression))
+}
+
+#[test]
+fn precedence_and_associativity() {
+    assert_eq!(parse("1 + 2 * 3").unwrap(), "(+ 1 (* 2 3))");
+    assert_eq!(parse("1 - 2 - 3").unwrap(), "(- (- 1 2) 3)");
+    assert_eq!(parse("2 ^ 3 ^ 4").unwrap(), "(^ 2 (^ 3 4))");
+    assert_eq!(parse("-a ^ b").unwrap(), "(^ (- a) b)");
+    assert_eq!(parse("-a!").unwrap(), "(- (! a))");
+    assert_eq!(parse("a == b + 1").unwrap(), "(== a (+ b 1))");
+}
+
+#[test]
+fn mixfix() {
+    assert_eq!(parse("a ? b : c ? d : e").unwrap(), "(? a b (? c d e))");
+    assert_eq!(parse("f(a, b + 1)[i]").unwrap(), "(index (call f [a (+ b 1)]) i)");
+    assert_eq!(parse("f()").unwrap(), "(call f [])");
+}
+
+#[test]
+fn spans() {
+    let mut parser = parser("x + -y * 2 ;");
+    let expression = parser.parse_expression::<Atom>(&operators(&parser), &mut ()).unwrap();
+    assert_eq!(expression.slice(), "x + -y * 2");
+    assert_eq!(parser.remaining(), " ;");
+
+    let Expression::Infix { right, .. } = &*expression else { panic!("expected an infix expression") };
+    assert_eq!(right.slice(), "-y * 2");
+    assert_eq!(right.span().byte_start, 4);
+}
+
+#[test]
+fn errors() {
+    let mut parser = parser("a == b == c");
+    let error = parser.parse_expression::<Atom>(&operators(&parser), &mut ()).unwrap_err();
+    assert_eq!(error.syntax().unwrap().span().byte_start, 7);
+    assert_eq!(parser.position(), 0);
+    assert!(parser.interner().symbol("a").is_none());
+
+    let error = parse("f(a b)").unwrap_err();
+    assert!(error.syntax().unwrap().expected().contains(&Expected::String(")".into())));
+}
+
+#[test]
+fn runtime_operators() {
+    let mut parser = Parser::<()>::new("a <> b");
+    let operator = parser.internalize("<>", ()).unwrap();
+    let operators = Operators::new().with_space().infix(operator, 0, Associativity::Left);
+
+    let expression = parser.parse_expression::<Atom>(&operators, &mut ()).unwrap();
+    assert_eq!(show(&expression), "(<> a b)");
+}
+
+#[test]
+fn word_operators() {
+    let mut parser = Parser::<()>::new("not index in notes");
+    let not = parser.internalize("not", ()).unwrap();
+    let is_in = parser.internalize("in", ()).unwrap();
+    let operators = Operators::new().with_space().prefix(not, 1).infix(is_in, 0, Associativity::Left);
+
+    let expression = parser.parse_expression::<Atom>(&operators, &mut ()).unwrap();
+    assert_eq!(show(&expression), "(in (not index) notes)");
+}
@@ -0,0 +1,126 @@
+use bytestring::ByteString;
+use crate::span::Span;
+
+#[cfg(test)]
+mod test;
+
+/// Tab width used by [SourceMap::new].
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// Index of where every line of a source string starts.
+/// 
+/// # Usage
+/// Built once per source and used to resolve byte indices and spans into lines and columns, 
+/// such as when reporting errors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceMap {
+    source: ByteString,
+    /// Byte index of the first character of every line, in order.
+    lines: Vec<usize>,
+    tab_width: usize
+}
+
+/// A position in a source string as a line and column.
+/// 
+/// # Usage
+/// Lines and columns start at 1. The column is available in several units because editors and 
+/// terminals count columns differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Location {
+    pub line: usize,
+    /// Column in characters, where a tab advances to the next multiple of the tab width.
+    pub column: usize,
+    /// Column in bytes.
+    pub byte_column: usize,
+    /// Column in UTF-16 code units, as used by the language server protocol.
+    pub utf16_column: usize
+}
+
+/// Where a span starts and ends in a source string as lines and columns.
+/// 
+/// # Usage
+/// The end location is the position right after the last character of the span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SpanLocation {
+    pub start: Location,
+    pub end: Location
+}
+
+impl SourceMap {
+    /// Index the lines of a source string, using [DEFAULT_TAB_WIDTH] for tabs.
+    pub fn new(source: impl Into<ByteString>) -> Self {
+        let source = source.into();
+        let lines = core::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        
+        Self {
+            source,
+            lines,
+            tab_width: DEFAULT_TAB_WIDTH
+        }
+    }
+    
+    /// Set how many columns a tab advances to, with 1 counting a tab as any other character.
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width.max(1);
+        self
+    }
+    
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+    
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+    
+    /// Content of a line without its line ending, with the first line being 1.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.lines.get(line.checked_sub(1)?)?;
+        let end = self.lines.get(line).map_or(self.source.len(), |next| next - 1);
+        
+        let content = &self.source[start..end];
+        Some(content.strip_suffix('\r').unwrap_or(content))
+    }
+    
+    /// Resolve a byte index into a location.
+    /// 
+    /// # Usage
+    /// An index past the end of the source resolves to the end of the source and an index inside 
+    /// of a character resolves to the start of that character.
+    pub fn location(&self, byte_index: usize) -> Location {
+        let byte_index = self.source.floor_char_boundary(byte_index);
+        let line = self.lines.partition_point(|&start| start <= byte_index);
+        let line_start = self.lines[line - 1];
+        
+        let mut location = Location {
+            line,
+            column: 1,
+            byte_column: byte_index - line_start + 1,
+            utf16_column: 1
+        };
+        
+        for character in self.source[line_start..byte_index].chars() {
+            location.column += match character {
+                '\t' => self.tab_width - (location.column - 1) % self.tab_width,
+                _ => 1
+            };
+            location.utf16_column += character.len_utf16();
+        }
+        
+        location
+    }
+    
+    /// Resolve the start and end of a span into locations.
+    pub fn resolve(&self, span: &Span) -> SpanLocation {
+        SpanLocation {
+            start: self.location(span.byte_start),
+            end: self.location(span.byte_end())
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::source_map::{Location, SourceMap};
+use crate::span::Span;
+
+#[test]
+fn line() {
+    let map = SourceMap::new("first\r\nsecond\n\nlast");
+
+    assert_eq!(map.line_count(), 4);
+    assert_eq!(map.line(1), Some("first"));
+    assert_eq!(map.line(2), Some("second"));
+    assert_eq!(map.line(3), Some(""));
+    assert_eq!(map.line(4), Some("last"));
+    assert_eq!(map.line(0), None);
+    assert_eq!(map.line(5), None);
+}
+
+#[test]
+fn location() {
+    let map = SourceMap::new("a\n\té𝄞x").with_tab_width(4);
+
+    assert_eq!(map.location(0), Location { line: 1, column: 1, byte_column: 1, utf16_column: 1 });
+    assert_eq!(map.location(2), Location { line: 2, column: 1, byte_column: 1, utf16_column: 1 });
+    assert_eq!(map.location(9), Location { line: 2, column: 7, byte_column: 8, utf16_column: 5 });
+    assert_eq!(map.location(100), map.location(10));
+}
+
+#[test]
+fn resolve() {
+    let map = SourceMap::new("let a = 1;\nlet b = ;");
+    let span = Span { start: 19, length: 1, byte_start: 19, byte_length: 1 };
+
+    let location = map.resolve(&span);
+    assert_eq!((location.start.line, location.start.column), (2, 9));
+    assert_eq!((location.end.line, location.end.column), (2, 10));
+}
@@ -17,7 +17,7 @@ pub struct Span {
     pub byte_length: usize
 }
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Clone, Copy, Error, PartialEq)]
 #[error("An arithmetic operation resulted in an overflow")]
 pub struct ArithmeticOverflow;
 
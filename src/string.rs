@@ -1,77 +1,347 @@
-use alloc::rc::Rc;
 use bytestring::ByteString;
 use indexmap::IndexMap;
-use std::cell::{Ref, RefCell};
 use std::ops::Deref;
 use thiserror::Error;
+use crate::keywords::KeywordTable;
+use crate::sync::{Lock, Shared};
+
+#[cfg(test)]
+mod test;
 
 pub type StringsMap<Token> = IndexMap<ByteString, Token>;
-pub type Strings<Token> = Rc<RefCell<StringsMap<Token>>>;
+pub type Strings<Token> = Shared<Lock<InternalizedStrings<Token>>>;
+/// Scopes pushed with [crate::Parser::push_scope], innermost last, which never change once they 
+/// are shared.
+pub type Scopes<Token> = Shared<[KeywordTable<Token>]>;
+
+/// Strings internalized while parsing, in the order they were internalized.
+#[derive(Debug, PartialEq)]
+pub struct InternalizedStrings<Token> {
+    /// Each string along with its token, which is shared so that it can be held without 
+    /// borrowing the strings, the generation the string was internalized in and the owner of the 
+    /// parser that internalized it.
+    entries: IndexMap<ByteString, (Shared<Token>, u32, usize)>,
+    /// How many strings were ever internalized, which is the generation of the next string. Every 
+    /// string has a different generation, so that the symbols of removed strings are told apart 
+    /// from the symbols of strings internalized in their place.
+    generation: u32
+}
 
+/// Handle to an internalized string, which is cheap to copy, compare and hash.
+/// 
+/// # Usage
+/// Resolved to its string and token through the [Interner] of the parser that internalized it. 
+/// Symbols of strings that were removed by rewinding a parser or rolling back a transaction 
+/// resolve to nothing from then on, even if a string is internalized in their place.
+/// 
+/// The keywords of the [KeywordTable] a parser was created from come first, so their symbols are 
+/// the same for every parser created from the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol {
+    index: u32,
+    generation: u32
+}
+
+/// View of the strings internalized by a parser, used to resolve [Symbol]s.
+/// 
+/// # Usage
+/// The interner only borrows the strings while resolving a symbol, and what it resolves to does 
+/// not borrow them at all, so strings can be internalized while either is held.
 #[derive(Debug)]
-pub struct TokenGuard<'a, Token> {
-    borrow: Ref<'a, StringsMap<Token>>,
-    index: usize
+pub struct Interner<'a, Token> {
+    keywords: &'a KeywordTable<Token>,
+    strings: &'a Strings<Token>,
+    scopes: &'a Scopes<Token>
 }
 
-impl<Token> Deref for TokenGuard<'_, Token> {
+/// Token of a string, which keeps the token alive without borrowing the strings it came from.
+#[derive(Debug, Clone)]
+pub struct TokenGuard<Token> {
+    source: TokenSource<Token>
+}
+
+#[derive(Debug, Clone)]
+enum TokenSource<Token> {
+    /// Table of keywords or of a scope, and the index of the string in it.
+    Keyword(KeywordTable<Token>, usize),
+    Internalized(Shared<Token>)
+}
+
+impl<Token> Deref for TokenGuard<Token> {
     type Target = Token;
 
     fn deref(&self) -> &Self::Target {
-        &self.borrow[self.index]
+        match &self.source {
+            TokenSource::Keyword(table, index) => table.token(*index),
+            TokenSource::Internalized(token) => token
+        }
+    }
+}
+
+/// Transaction on internalized strings, started with [crate::Parser::begin_transaction].
+/// 
+/// # Usage
+/// Dropping the transaction without committing it rolls it back.
+#[derive(Debug)]
+#[must_use = "the transaction is rolled back when it is dropped"]
+pub struct Transaction<Token> {
+    strings: Strings<Token>,
+    owner: usize,
+    /// Generation of the first string internalized in the transaction.
+    generation: u32,
+    committed: bool
+}
+
+impl<Token> InternalizedStrings<Token> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    /// Strings and their tokens, in the order they were internalized.
+    pub fn iter(&self) -> impl Iterator<Item = (&ByteString, &Token)> {
+        self.entries.iter().map(|(string, (token, ..))| (string, &**token))
+    }
+    
+    /// Index of a string and the generation it was internalized in.
+    fn find(&self, string: &str) -> Option<(usize, u32)> {
+        let (index, _, (_, generation, _)) = self.entries.get_full(string)?;
+        Some((index, *generation))
+    }
+    
+    /// The string at an index and its token, if it was internalized in a generation.
+    fn get(&self, index: usize, generation: u32) -> Option<(&ByteString, &Shared<Token>)> {
+        let (string, (token, internalized, _)) = self.entries.get_index(index)?;
+        (*internalized == generation).then_some((string, token))
+    }
+    
+    /// Internalize a string that was not internalized yet for the parsers of an owner, returning 
+    /// its index and generation.
+    pub(crate) fn push(&mut self, string: ByteString, token: Token, owner: usize) -> (usize, u32) {
+        let generation = self.generation;
+        let (index, previous) = self.entries.insert_full(string, (Shared::new(token), generation, owner));
+        debug_assert!(previous.is_none(), "strings are only internalized once");
+        
+        self.generation = generation.wrapping_add(1);
+        (index, generation)
+    }
+    
+    /// The generation of the next string to be internalized.
+    pub(crate) fn generation(&self) -> u32 {
+        self.generation
+    }
+    
+    /// Remove the strings the parsers of an owner internalized since a generation.
+    /// 
+    /// # Usage
+    /// Strings are removed from the last one and only until a string of another owner is found, 
+    /// which is kept along with every string before it so that the symbols of the strings of 
+    /// other parsers sharing the strings stay the same.
+    pub(crate) fn truncate(&mut self, since: u32, owner: usize) {
+        let internalized = self.generation.wrapping_sub(since);
+        
+        while let Some((_, (_, generation, internalizer))) = self.entries.last() {
+            if *internalizer != owner || generation.wrapping_sub(since) >= internalized { break }
+            self.entries.pop();
+        }
+    }
+}
+
+impl<Token> Clone for InternalizedStrings<Token> {
+    fn clone(&self) -> Self {
+        Self { entries: self.entries.clone(), generation: self.generation }
+    }
+}
+
+impl<Token> Default for InternalizedStrings<Token> {
+    fn default() -> Self {
+        Self { entries: IndexMap::new(), generation: 0 }
+    }
+}
+
+impl Symbol {
+    pub(crate) fn new(index: usize, generation: u32) -> Self {
+        let index = u32::try_from(index).expect("fewer than `u32::MAX` strings are internalized");
+        Self { index, generation }
+    }
+    
+    /// Index of the string in the order strings were internalized.
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+}
+
+impl<'a, Token> Interner<'a, Token> {
+    pub(crate) fn new(keywords: &'a KeywordTable<Token>, strings: &'a Strings<Token>, scopes: &'a Scopes<Token>) -> Self {
+        Self { keywords, strings, scopes }
+    }
+    
+    /// The string of a symbol, if it is a keyword or was internalized.
+    /// 
+    /// # Usage
+    /// The string is an owned handle to the stored string rather than a copy of it, so it is as 
+    /// cheap to get and clone as a reference count and never allocates.
+    pub fn resolve(&self, symbol: Symbol) -> Option<ByteString> {
+        self.get(symbol).map(|(string, _)| string)
+    }
+    
+    /// The token of a symbol in the innermost scope the parser is in that has its string, or the 
+    /// token it was internalized with if no scope has it.
+    pub fn token(&self, symbol: Symbol) -> Option<TokenGuard<Token>> {
+        let (string, source) = self.get(symbol)?;
+        let source = match scoped(self.scopes, &string) {
+            Some((scope, index)) => TokenSource::Keyword(scope, index),
+            None => source
+        };
+        Some(TokenGuard { source })
+    }
+    
+    fn get(&self, symbol: Symbol) -> Option<(ByteString, TokenSource<Token>)> {
+        resolve(self.keywords, &self.strings.borrow(), symbol).map(|(string, source)| (string.clone(), source))
+    }
+    
+    /// The symbol of a string, if it is a keyword or was internalized.
+    pub fn symbol(&self, string: &str) -> Option<Symbol> {
+        find(self.keywords, &self.strings.borrow(), string)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.keywords.len() + self.strings.borrow().len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The innermost scope that has a string, and the index of the string in it.
+fn scoped<Token>(scopes: &[KeywordTable<Token>], string: &str) -> Option<(KeywordTable<Token>, usize)> {
+    scopes
+        .iter()
+        .rev()
+        .find_map(|scope| Some((scope.clone(), scope.index_of(string)?)))
+}
+
+/// Symbol of a string among the keywords followed by the internalized strings.
+fn find<Token>(keywords: &KeywordTable<Token>, strings: &InternalizedStrings<Token>, string: &str) -> Option<Symbol> {
+    if let Some(index) = keywords.index_of(string) { return Some(Symbol::new(index, 0)) }
+    
+    let (index, generation) = strings.find(string)?;
+    Some(Symbol::new(keywords.len() + index, generation))
+}
+
+/// String and token of a symbol, unless its string was removed.
+fn resolve<'a, Token>(keywords: &'a KeywordTable<Token>, strings: &'a InternalizedStrings<Token>, symbol: Symbol) -> Option<(&'a ByteString, TokenSource<Token>)> {
+    match symbol.index().checked_sub(keywords.len()) {
+        Some(index) => {
+            let (string, token) = strings.get(index, symbol.generation)?;
+            Some((string, TokenSource::Internalized(token.clone())))
+        },
+        None => {
+            let (string, _) = keywords.get_index(symbol.index())?;
+            Some((string, TokenSource::Keyword(keywords.clone(), symbol.index())))
+        }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct String<Token> {
+    pub(super) keywords: KeywordTable<Token>,
+    pub(super) scopes: Scopes<Token>,
     pub(super) strings: Strings<Token>,
+    /// Owner of the parser that created this string, which internalizes it for the parser.
+    pub(super) owner: usize,
     pub(super) slice: ByteString,
-    pub(super) index: Option<usize>
+    /// Symbol this string was internalized as, which is stale if it was removed since.
+    pub(super) symbol: Option<Symbol>
 }
 
 #[derive(Debug, Error, PartialEq)]
 #[error("Cannot re-internalize a string")]
 pub struct ReInternalizationError;
 
+impl<Token> Transaction<Token> {
+    pub(crate) fn new(strings: &Strings<Token>, owner: usize) -> Self {
+        Self {
+            strings: strings.clone(),
+            owner,
+            generation: strings.borrow().generation(),
+            committed: false
+        }
+    }
+    
+    /// Keep the strings internalized since the transaction began, unless a transaction it is 
+    /// inside of is rolled back.
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+    
+    /// Remove the strings internalized since the transaction began.
+    pub fn rollback(self) {
+        drop(self);
+    }
+}
+
+impl<Token> Drop for Transaction<Token> {
+    fn drop(&mut self) {
+        if !self.committed { self.strings.borrow_mut().truncate(self.generation, self.owner) }
+    }
+}
+
 impl<Token> String<Token> {
-    pub fn try_internalize(&mut self, on_create: impl for<'a> FnOnce(&'a str) -> Option<Token>) -> Result<Option<TokenGuard<Token>>, ReInternalizationError> {
-        let None = self.index else { return Err(ReInternalizationError) };
-        
+    /// Internalize this string, creating its token if it was not internalized yet.
+    /// 
+    /// # Error
+    /// Fails if this string was already internalized and was not removed since. Returns nothing 
+    /// if creating the token returned nothing, in which case the string is not internalized.
+    pub fn try_internalize(&mut self, on_create: impl for<'a> FnOnce(&'a str) -> Option<Token>) -> Result<Option<Symbol>, ReInternalizationError> {
         let mut strings = self.strings.borrow_mut();
-        let index = match strings.entry(self.slice.clone()) {
-            indexmap::map::Entry::Occupied(mapping) => {
-                mapping.index()
-            },
-            indexmap::map::Entry::Vacant(mapping) => {
-                let index = mapping.index();
+        if self.symbol.is_some_and(|symbol| resolve(&self.keywords, &strings, symbol).is_some()) {
+            return Err(ReInternalizationError);
+        }
+        
+        let symbol = match find(&self.keywords, &strings, &self.slice) {
+            Some(symbol) => symbol,
+            None => {
                 let Some(token) = on_create(&self.slice) else { return Ok(None) };
-                let _ = mapping.insert(token);
-                index
+                let (index, generation) = strings.push(self.slice.clone(), token, self.owner);
+                Symbol::new(self.keywords.len() + index, generation)
             }
         };
-        drop(strings);
         
-        self.index = Some(index);
-        Ok(Some(TokenGuard {
-            index,
-            borrow: self.strings.borrow()
-        }))
+        self.symbol = Some(symbol);
+        Ok(Some(symbol))
     }
     
-    pub fn token(&self) -> Option<TokenGuard<'_, Token>> {
-        let borrow = RefCell::borrow(&self.strings);
+    /// The token of this string in the innermost scope that has it among the scopes it was created 
+    /// in, or the token it was internalized with if no scope has it.
+    pub fn token(&self) -> Option<TokenGuard<Token>> {
+        if let Some((scope, index)) = scoped(&self.scopes, &self.slice) {
+            return Some(TokenGuard { source: TokenSource::Keyword(scope, index) });
+        }
         
-        let index = if let Some(index) = self.index {
-            index
-        } else {
-            let index = borrow.get_full(&self.slice)?.0;
-            index
-        };
+        let strings = self.strings.borrow();
+        let symbol = self.find(&strings)?;
+        let (_, source) = resolve(&self.keywords, &strings, symbol)?;
+        Some(TokenGuard { source })
+    }
+    
+    /// The symbol of this string, if it is a keyword or was internalized.
+    pub fn symbol(&self) -> Option<Symbol> {
+        self.find(&self.strings.borrow())
+    }
+    
+    fn find(&self, strings: &InternalizedStrings<Token>) -> Option<Symbol> {
+        // The symbol is stale if its string was removed by rewinding a parser, in which case the 
+        // string may have been internalized again since.
+        if let Some(symbol) = self.symbol && resolve(&self.keywords, strings, symbol).is_some() {
+            return Some(symbol);
+        }
         
-        Some(TokenGuard {
-            borrow,
-            index
-        })
+        find(&self.keywords, strings, &self.slice)
     }
 }
 
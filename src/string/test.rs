@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+use bytestring::ByteString;
+use crate::{Parsable, Parser, ParserString};
+use crate::error::Error;
+use crate::keywords::KeywordTable;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Keyword,
+    Identifier
+}
+
+#[test]
+fn symbols() {
+    let mut parser = Parser::<Token>::new("let x");
+    let keyword = parser.internalize("let", Token::Keyword).unwrap();
+    
+    let mut word = parser.parse_while(char::is_alphabetic);
+    assert_eq!(word.try_internalize(|_| Some(Token::Identifier)).unwrap(), Some(keyword));
+    assert_eq!(word.symbol(), Some(keyword));
+    
+    parser.parse_while(char::is_whitespace);
+    let mut identifier = parser.parse_while(char::is_alphabetic);
+    let symbol = identifier.try_internalize(|_| Some(Token::Identifier)).unwrap().unwrap();
+    assert_ne!(symbol, keyword);
+    assert_eq!(HashSet::from([keyword, symbol, keyword]).len(), 2);
+    
+    let interner = parser.interner();
+    assert_eq!(interner.resolve(keyword).as_deref(), Some("let"));
+    assert_eq!(interner.token(symbol).as_deref(), Some(&Token::Identifier));
+    assert_eq!(interner.symbol("x"), Some(symbol));
+    assert_eq!(interner.symbol("y"), None);
+}
+
+#[test]
+fn internalize_while_token_is_held() {
+    let mut parser = Parser::<Token>::new("x y");
+    let symbol = parser.internalize("let", Token::Keyword).unwrap();
+    let mut x = parser.parse_while(char::is_alphabetic);
+    x.try_internalize(|_| Some(Token::Identifier)).unwrap();
+    
+    let token = x.token().unwrap();
+    let interned = parser.interner().token(symbol).unwrap();
+    parser.parse_while(char::is_whitespace);
+    let mut y = parser.parse_while(char::is_alphabetic);
+    assert!(y.try_internalize(|_| Some(Token::Identifier)).unwrap().is_some());
+    parser.internalize("if", Token::Keyword).unwrap();
+    
+    assert_eq!(*token, Token::Identifier);
+    assert_eq!(*interned, Token::Keyword);
+}
+
+#[test]
+fn rewound_symbols() {
+    let mut parser = Parser::<Token>::new("x");
+    let checkpoint = parser.checkpoint();
+    
+    let mut identifier = parser.parse_while(char::is_alphabetic);
+    let symbol = identifier.try_internalize(|_| Some(Token::Identifier)).unwrap().unwrap();
+    parser.rewind(checkpoint);
+    
+    assert_eq!(parser.interner().resolve(symbol), None);
+    assert_eq!(identifier.symbol(), None);
+    
+    // Another string now has the index the identifier had.
+    let other = parser.internalize("y", Token::Keyword).unwrap();
+    assert_eq!(other.index(), symbol.index());
+    assert_eq!(parser.interner().resolve(symbol), None);
+    assert_eq!(identifier.symbol(), None);
+    assert!(identifier.token().is_none());
+    
+    // The identifier can be internalized again, getting a new symbol.
+    let internalized = identifier.try_internalize(|_| Some(Token::Identifier)).unwrap().unwrap();
+    assert_ne!(internalized, symbol);
+    assert_eq!(identifier.symbol(), Some(internalized));
+    assert!(identifier.try_internalize(|_| Some(Token::Identifier)).is_err());
+}
+
+#[test]
+fn transactions() {
+    let mut parser = Parser::<Token>::new("a b c");
+    let outer = parser.begin_transaction();
+    let mut a = parser.parse_while(char::is_alphabetic);
+    let symbol = a.try_internalize(|_| Some(Token::Identifier)).unwrap().unwrap();
+    
+    let inner = parser.begin_transaction();
+    parser.parse_while(char::is_whitespace);
+    let mut b = parser.parse_while(char::is_alphabetic);
+    b.try_internalize(|_| Some(Token::Identifier)).unwrap();
+    inner.commit();
+    
+    let rolled_back = parser.begin_transaction();
+    parser.parse_while(char::is_whitespace);
+    let mut c = parser.parse_while(char::is_alphabetic);
+    c.try_internalize(|_| Some(Token::Identifier)).unwrap();
+    rolled_back.rollback();
+    
+    assert!(a.token().is_some() && b.token().is_some() && c.token().is_none());
+    
+    // Rolling back the outer transaction also removes what the committed one kept.
+    drop(outer);
+    assert!(parser.interner().is_empty());
+    assert!(a.token().is_none() && b.token().is_none());
+    
+    // The symbol stays stale once its string is internalized again.
+    parser.internalize("a", Token::Keyword).unwrap();
+    assert_eq!(parser.interner().resolve(symbol), None);
+}
+
+fn scope(entries: &[(&str, Token)]) -> KeywordTable<Token> {
+    entries.iter().map(|&(string, token)| (ByteString::from(string), token)).collect()
+}
+
+#[test]
+fn scopes() {
+    let mut parser = Parser::<Token>::new("let x let");
+    let symbol = parser.internalize("let", Token::Keyword).unwrap();
+    
+    parser.push_scope(scope(&[("let", Token::Identifier)]));
+    parser.push_scope(scope(&[("x", Token::Keyword)]));
+    let mut word = parser.parse_while(char::is_alphabetic);
+    word.try_internalize(|_| None).unwrap();
+    assert_eq!(word.token().as_deref(), Some(&Token::Identifier));
+    assert_eq!(parser.interner().token(symbol).as_deref(), Some(&Token::Identifier));
+    
+    // Strings only in a scope have a token but no symbol.
+    parser.parse_while(char::is_whitespace);
+    let x = parser.parse_while(char::is_alphabetic);
+    assert_eq!(x.token().as_deref(), Some(&Token::Keyword));
+    assert_eq!(x.symbol(), None);
+    
+    // Strings keep the scopes they were created in.
+    parser.pop_scope();
+    parser.pop_scope();
+    assert_eq!(x.token().as_deref(), Some(&Token::Keyword));
+    assert_eq!(word.token().as_deref(), Some(&Token::Identifier));
+    assert_eq!(parser.interner().token(symbol).as_deref(), Some(&Token::Keyword));
+    assert_eq!(word.symbol(), Some(symbol));
+    assert!(parser.pop_scope().is_none());
+    
+    parser.parse_while(char::is_whitespace);
+    let outside = parser.parse_while(char::is_alphabetic);
+    assert_eq!(outside.token().as_deref(), Some(&Token::Keyword));
+}
+
+#[test]
+fn rewound_scopes() {
+    let mut parser = Parser::<Token>::new("x");
+    parser.push_scope(scope(&[("x", Token::Keyword)]));
+    let checkpoint = parser.checkpoint();
+    
+    parser.push_scope(scope(&[("x", Token::Identifier)]));
+    parser.push_scope(scope(&[("y", Token::Identifier)]));
+    parser.rewind(checkpoint);
+    
+    let x = parser.parse_while(char::is_alphabetic);
+    assert_eq!(x.token().as_deref(), Some(&Token::Keyword));
+    assert!(parser.pop_scope().is_some());
+    assert!(parser.pop_scope().is_none());
+}
+
+#[test]
+fn rewound_popped_scopes() {
+    let mut parser = Parser::<Token>::new("x");
+    parser.push_scope(scope(&[("x", Token::Keyword)]));
+    let checkpoint = parser.checkpoint();
+    
+    // The scope that replaces the popped scope is left again, even though as many scopes are 
+    // entered.
+    parser.pop_scope();
+    parser.push_scope(scope(&[("x", Token::Identifier)]));
+    parser.rewind(checkpoint.clone());
+    
+    let x = parser.parse_while(char::is_alphabetic);
+    assert_eq!(x.token().as_deref(), Some(&Token::Keyword));
+    
+    parser.pop_scope();
+    parser.rewind(checkpoint);
+    
+    let x = parser.parse_while(char::is_alphabetic);
+    assert_eq!(x.token().as_deref(), Some(&Token::Keyword));
+    assert!(parser.pop_scope().is_some());
+    assert!(parser.pop_scope().is_none());
+}
+
+/// Parses a run of letters.
+#[derive(Debug)]
+struct Word {
+    word: ParserString<Token>
+}
+
+impl Parsable for Word {
+    type Error = ();
+    type Token = Token;
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, _: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        Ok(Self { word: parser.parse_while(char::is_alphabetic) })
+    }
+}
+
+#[test]
+fn parse_scoped() {
+    let mut parser = Parser::<Token>::new("x");
+    let node = parser.parse_scoped::<Word>(scope(&[("x", Token::Keyword)]), &mut ()).unwrap();
+    assert_eq!(node.word.token().as_deref(), Some(&Token::Keyword));
+    assert!(parser.pop_scope().is_none());
+    
+    // Scopes entered by a clone do not apply to the parser it was cloned from.
+    let mut clone = parser.clone();
+    clone.push_scope(scope(&[("x", Token::Identifier)]));
+    assert!(parser.parse::<Word>(&mut ()).unwrap().word.token().is_none());
+}
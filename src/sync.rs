@@ -0,0 +1,128 @@
+//! Shared state of parsers, which is thread-safe when the `sync` feature is enabled.
+//!
+//! Without the feature, state is shared with [alloc::rc::Rc] and [core::cell::RefCell], which are
+//! cheaper but keep parsers and their strings on one thread. With it, state is shared with
+//! [alloc::sync::Arc] and [std::sync::RwLock] so that [crate::Parser], [crate::ParserString] and
+//! [crate::Node] are `Send + Sync`, such as for parsing many files against one keyword table.
+//!
+//! Parsers that parse in parallel are cloned from one parser, sharing its internalized strings 
+//! behind a lock so that a string has the same [crate::string::Symbol] on every thread, or 
+//! created from the same [crate::keywords::KeywordTable], which never changes and is shared 
+//! without a lock.
+
+#[cfg(not(feature = "sync"))]
+use core::cell::{Ref, RefCell, RefMut};
+#[cfg(feature = "sync")]
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Pointer to state shared between parsers.
+#[cfg(not(feature = "sync"))]
+pub type Shared<T> = alloc::rc::Rc<T>;
+/// Pointer to state shared between parsers.
+#[cfg(feature = "sync")]
+pub type Shared<T> = alloc::sync::Arc<T>;
+
+#[cfg(not(feature = "sync"))]
+pub type ReadGuard<'a, T> = Ref<'a, T>;
+#[cfg(feature = "sync")]
+pub type ReadGuard<'a, T> = RwLockReadGuard<'a, T>;
+
+#[cfg(not(feature = "sync"))]
+pub type WriteGuard<'a, T> = RefMut<'a, T>;
+#[cfg(feature = "sync")]
+pub type WriteGuard<'a, T> = RwLockWriteGuard<'a, T>;
+
+/// Bound on values kept in shared state, which must be `Send + Sync` when the `sync` feature is
+/// enabled and can be anything otherwise.
+#[cfg(not(feature = "sync"))]
+pub trait SendSync {}
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> SendSync for T {}
+
+/// Bound on values kept in shared state, which must be `Send + Sync` when the `sync` feature is
+/// enabled and can be anything otherwise.
+#[cfg(feature = "sync")]
+pub trait SendSync: Send + Sync {}
+#[cfg(feature = "sync")]
+impl<T: Send + Sync + ?Sized> SendSync for T {}
+
+/// Value of any type kept in shared state.
+#[cfg(not(feature = "sync"))]
+pub(crate) type AnyBox = Box<dyn core::any::Any>;
+#[cfg(feature = "sync")]
+pub(crate) type AnyBox = Box<dyn core::any::Any + Send + Sync>;
+
+/// Function checking whether parsing was cancelled.
+#[cfg(not(feature = "sync"))]
+pub(crate) type CancellationCheck = dyn Fn() -> bool;
+#[cfg(feature = "sync")]
+pub(crate) type CancellationCheck = dyn Fn() -> bool + Send + Sync;
+
+/// Mutable state shared between parsers.
+///
+/// # Usage
+/// Borrowing panics if the value is already borrowed mutably, as with a [core::cell::RefCell]. 
+/// With the `sync` feature, borrowing instead waits for other threads to release the value, and 
+/// may deadlock if the same thread is holding it.
+#[derive(Debug, Default)]
+pub struct Lock<T> {
+    #[cfg(not(feature = "sync"))]
+    value: RefCell<T>,
+    #[cfg(feature = "sync")]
+    value: RwLock<T>
+}
+
+impl<T> Lock<T> {
+    pub fn new(value: T) -> Self {
+        #[cfg(not(feature = "sync"))]
+        return Self { value: RefCell::new(value) };
+        #[cfg(feature = "sync")]
+        return Self { value: RwLock::new(value) };
+    }
+
+    pub fn borrow(&self) -> ReadGuard<'_, T> {
+        #[cfg(not(feature = "sync"))]
+        return self.value.borrow();
+        // A panic while the value was borrowed cannot leave it half-modified in a way that
+        // matters more than the panic itself, so the poison is ignored.
+        #[cfg(feature = "sync")]
+        return self.value.read().unwrap_or_else(PoisonError::into_inner);
+    }
+
+    pub fn borrow_mut(&self) -> WriteGuard<'_, T> {
+        #[cfg(not(feature = "sync"))]
+        return self.value.borrow_mut();
+        #[cfg(feature = "sync")]
+        return self.value.write().unwrap_or_else(PoisonError::into_inner);
+    }
+
+    pub fn replace(&self, value: T) -> T {
+        core::mem::replace(&mut *self.borrow_mut(), value)
+    }
+
+    pub fn take(&self) -> T where T: Default {
+        self.replace(T::default())
+    }
+}
+
+impl<T: Copy> Lock<T> {
+    pub fn get(&self) -> T {
+        *self.borrow()
+    }
+
+    pub fn set(&self, value: T) {
+        *self.borrow_mut() = value;
+    }
+}
+
+impl<T: PartialEq> PartialEq for Lock<T> {
+    fn eq(&self, other: &Self) -> bool {
+        *self.borrow() == *other.borrow()
+    }
+}
+
+impl<T: Clone> Clone for Lock<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.borrow().clone())
+    }
+}
@@ -0,0 +1,384 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use crate::{ExpectError, Limits, Node, Parsable, Parser};
+use crate::combinator::Many;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::error::{Cancelled, Error, ErrorKind, Expected, Found, LeftRecursion, Limit, LimitExceeded};
+
+/// Parses "ab" but fails after consuming the "a" when the "b" is missing.
+#[derive(Debug)]
+struct AB;
+
+impl Parsable for AB {
+    type Error = ();
+    type Token = ();
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, _: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        parser.expect_char('a').map_err(|_| parser.expected([Expected::Char('a')]))?;
+        parser.expect_char('b').map_err(|_| parser.expected([Expected::Char('b')]))?;
+        Ok(Self)
+    }
+}
+
+#[test]
+fn rewind() {
+    let mut parser = Parser::<()>::new("abc");
+    let checkpoint = parser.checkpoint();
+
+    parser.expect_char('a').unwrap();
+    parser.expect_char('b').unwrap();
+    parser.rewind(checkpoint);
+
+    assert_eq!(parser.span().byte_end(), 0);
+    assert_eq!(parser.expect_char('a'), Ok(()));
+}
+
+#[test]
+fn parse_rewinds_on_error() {
+    let mut parser = Parser::<()>::new("ac");
+
+    assert!(parser.parse::<AB>(&mut ()).is_err());
+    assert_eq!(parser.span().byte_end(), 0);
+    assert_eq!(parser.expect_char('a'), Ok(()));
+    assert_eq!(parser.expect_char('c'), Ok(()));
+}
+
+#[test]
+fn lookahead() {
+    let mut parser = Parser::<()>::new("aé!");
+    parser.expect_char('a').unwrap();
+
+    assert_eq!(parser.position(), 1);
+    assert_eq!(parser.peek(), Some('é'));
+    assert_eq!(parser.remaining(), "é!");
+    assert_eq!(parser.span().length, 1);
+}
+
+#[test]
+fn expect_str() {
+    let mut parser = Parser::<()>::new("HTTPS://rust.rs");
+
+    assert_eq!(parser.expect_str("HTTP!"), Err(ExpectError::Unexpected));
+    assert_eq!(parser.position(), 0);
+
+    let scheme = parser.expect_any_str_ignore_case(&["http", "https", "ws"]).unwrap();
+    assert_eq!(&*scheme, "HTTPS");
+    assert_eq!(parser.span().length, 5);
+
+    let separator = parser.expect_any_str(&[":", "://"]).unwrap();
+    assert_eq!(&*separator, "://");
+    assert_eq!(parser.remaining(), "rust.rs");
+}
+
+/// Parses "a" and reports a warning if it is followed by a "!".
+#[derive(Debug)]
+struct Loud;
+
+impl Parsable for Loud {
+    type Error = ();
+    type Token = ();
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, _: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        parser.expect_char('a').map_err(|_| parser.expected([Expected::Char('a')]))?;
+        if parser.expect_char('!').is_ok() {
+            parser.report(Diagnostic::warning("unnecessary emphasis"));
+        }
+        Ok(Self)
+    }
+}
+
+#[test]
+fn diagnostics() {
+    let mut parser = Parser::<()>::new("a!a!b");
+
+    let (result, diagnostics) = parser.parse_with_diagnostics::<Many<Loud>>(&mut ());
+    assert_eq!(result.unwrap().items.len(), 2);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+
+    let checkpoint = parser.checkpoint();
+    parser.report(Diagnostic::error("discarded"));
+    parser.rewind(checkpoint);
+    assert!(parser.take_diagnostics().is_empty());
+}
+
+/// `difference = difference "-" digit | digit`, parsed as left-recursive unless the data is 
+/// false, in which case the recursion is guarded.
+#[derive(Debug, Clone)]
+enum Difference {
+    Subtract(Box<Node<Difference>>),
+    Digit
+}
+
+impl Parsable for Difference {
+    type Error = ();
+    type Token = ();
+    type Data = bool;
+
+    fn parse(parser: &mut Parser<Self::Token>, left_recursive: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let checkpoint = parser.checkpoint();
+        let left = match *left_recursive {
+            true => parser.parse_left_recursive::<Self>(left_recursive),
+            false => parser.parse_guarded::<Self>(left_recursive, 0)
+        };
+        
+        match left {
+            Ok(left) if parser.expect_char('-').is_ok() => {
+                parser.parse_while(|character| character.is_ascii_digit());
+                return Ok(Self::Subtract(Box::new(left)));
+            },
+            Err(error @ Error::LeftRecursion(_)) => return Err(error),
+            _ => parser.rewind(checkpoint)
+        }
+        
+        if parser.parse_while(|character| character.is_ascii_digit()).is_empty() {
+            return Err(parser.expected([Expected::Token("digit".into())]));
+        }
+        Ok(Self::Digit)
+    }
+}
+
+#[test]
+fn left_recursion() {
+    let mut parser = Parser::<()>::new("1-2-3");
+    let difference = parser.parse_left_recursive::<Difference>(&mut true).unwrap();
+    assert_eq!(difference.slice(), "1-2-3");
+    assert_eq!(parser.position(), 5);
+    
+    let Difference::Subtract(left) = &*difference else { panic!("expected a subtraction") };
+    assert_eq!(left.slice(), "1-2");
+    let Difference::Subtract(left) = &***left else { panic!("expected a subtraction") };
+    assert_eq!(left.slice(), "1");
+    assert!(matches!(***left, Difference::Digit));
+
+    let mut parser = Parser::<()>::new("1-2");
+    let error = parser.parse::<Difference>(&mut false).unwrap_err();
+    assert!(matches!(error, Error::LeftRecursion(LeftRecursion { span, .. }) if span.byte_start == 0));
+    assert_eq!(parser.position(), 0);
+}
+
+/// `sum = product ("+" product)*` and `product = digit ("*" digit)*`, parsed as the same node with 
+/// the data telling them apart.
+#[derive(Debug)]
+struct Precedence;
+
+impl Parsable for Precedence {
+    type Error = ();
+    type Token = ();
+    type Data = u8;
+
+    fn parse(parser: &mut Parser<Self::Token>, level: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        let operator = match *level {
+            0 => '+',
+            1 => '*',
+            _ => return match parser.parse_while(|character| character.is_ascii_digit()).is_empty() {
+                true => Err(parser.expected([Expected::Token("digit".into())])),
+                false => Ok(Self)
+            }
+        };
+        
+        let mut operand = *level + 1;
+        parser.parse::<Self>(&mut operand)?;
+        while parser.expect_char(operator).is_ok() {
+            parser.parse::<Self>(&mut operand)?;
+        }
+        Ok(Self)
+    }
+}
+
+#[test]
+fn parse_again_at_same_position() {
+    let mut parser = Parser::<()>::new("1*2+3");
+    let node = parser.parse::<Precedence>(&mut 0).unwrap();
+    assert_eq!(node.slice(), "1*2+3");
+    assert_eq!(parser.position(), 5);
+}
+
+/// `nested = "(" nested ")" | ""`.
+#[derive(Debug)]
+struct Nested;
+
+impl Parsable for Nested {
+    type Error = ();
+    type Token = ();
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, data: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        if parser.expect_char('(').is_ok() {
+            parser.parse::<Self>(data)?;
+            parser.expect_char(')').map_err(|_| parser.expected([Expected::Char(')')]))?;
+        }
+        Ok(Self)
+    }
+}
+
+#[test]
+fn limits() {
+    let source = "(".repeat(100_000) + &")".repeat(100_000);
+    let mut parser = Parser::<()>::new(source).with_limits(Limits { depth: Some(100), steps: None });
+    let error = parser.parse::<Nested>(&mut ()).unwrap_err();
+    assert!(matches!(error, Error::LimitExceeded(LimitExceeded { limit: Limit::Depth, span }) if span.byte_start == 100));
+    assert_eq!(parser.position(), 0);
+    
+    let mut parser = Parser::<()>::new("((()))").with_limits(Limits { depth: Some(4), steps: None });
+    parser.parse::<Nested>(&mut ()).unwrap();
+    
+    // The error is not mistaken for the end of the repetition.
+    let mut parser = Parser::<()>::new("ab".repeat(100)).with_limits(Limits { depth: None, steps: Some(10) });
+    let error = parser.parse::<Many<AB>>(&mut ()).unwrap_err();
+    assert!(matches!(error, Error::LimitExceeded(LimitExceeded { limit: Limit::Steps, .. })));
+    assert_eq!(parser.steps(), 11);
+
+    let mut parser = Parser::<()>::new("b").with_limits(Limits { depth: None, steps: Some(1) });
+    let error = parser.parse_first_of::<_, _, ()>(&mut (), &[
+        |parser, _| parser.expect_char('a').map_err(|_| parser.expected([Expected::Char('a')])),
+        |parser, _| parser.expect_char('b').map_err(|_| parser.expected([Expected::Char('b')]))
+    ]).unwrap_err();
+    assert!(matches!(error, Error::LimitExceeded(LimitExceeded { limit: Limit::Steps, .. })));
+}
+
+/// Parses a run of letters.
+#[derive(Debug)]
+struct Word;
+
+impl Parsable for Word {
+    type Error = ();
+    type Token = ();
+    type Data = ();
+
+    fn parse(parser: &mut Parser<Self::Token>, _: &mut Self::Data) -> Result<Self, Error<Self::Error>> {
+        parser.parse_while(char::is_alphabetic);
+        Ok(Self)
+    }
+}
+
+#[test]
+fn cancellation() {
+    let flag = Arc::new(AtomicBool::new(false));
+    let mut parser = Parser::<()>::new("abab").with_cancellation(flag.clone());
+    parser.parse::<AB>(&mut ()).unwrap();
+    
+    flag.store(true, Ordering::Relaxed);
+    let error = parser.parse::<AB>(&mut ()).unwrap_err();
+    assert!(matches!(error, Error::Cancelled(Cancelled { span }) if span.byte_start == 2));
+    assert!(parser.is_cancelled());
+    
+    // Cancelled on the second check, which is while consuming the letters.
+    let checks = Arc::new(AtomicUsize::new(0));
+    let counted = checks.clone();
+    let mut parser = Parser::<()>::new("a".repeat(10_000)).with_cancellation_check(move || {
+        counted.fetch_add(1, Ordering::Relaxed) >= 1
+    });
+    
+    let error = parser.parse::<Word>(&mut ()).unwrap_err();
+    assert!(matches!(error, Error::Cancelled(Cancelled { span }) if span.byte_start == 4096));
+    assert_eq!(parser.position(), 0);
+    assert_eq!(checks.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn parse_complete() {
+    let mut parser = Parser::<()>::new("ab");
+    parser.parse_complete::<AB>(&mut ()).unwrap();
+    assert!(parser.is_eof());
+    
+    let mut parser = Parser::<()>::new("abcd");
+    let error = parser.parse_complete::<AB>(&mut ()).unwrap_err();
+    let error = error.syntax().unwrap();
+    assert_eq!(error.kind(), &ErrorKind::TrailingInput);
+    assert_eq!(error.span().byte_range(), 2..4);
+    assert_eq!(error.found(), Some(&Found::Char('c')));
+    assert_eq!(parser.position(), 0);
+    
+    parser.parse::<AB>(&mut ()).unwrap();
+    assert!(!parser.is_eof());
+    assert!(parser.expect_eof::<()>().is_err());
+}
+
+#[test]
+fn clones_parse_apart() {
+    let mut parser = Parser::<()>::new("abab");
+    parser.report(Diagnostic::warning("before"));
+    
+    let mut clone = parser.clone();
+    let checkpoint = clone.checkpoint();
+    clone.parse::<AB>(&mut ()).unwrap();
+    let kept = clone.internalize("y", ()).unwrap();
+    clone.report(Diagnostic::warning("after"));
+    assert_eq!(parser.interner().resolve(kept).as_deref(), Some("y"));
+    
+    // Rewinding the clone does not remove what this parser internalized in the meantime.
+    let symbol = parser.internalize("x", ()).unwrap();
+    clone.rewind(checkpoint);
+    assert_eq!(clone.interner().resolve(symbol).as_deref(), Some("x"));
+    
+    assert_eq!(parser.position(), 0);
+    assert_eq!(parser.steps(), 0);
+    assert_eq!(parser.take_diagnostics().len(), 1);
+    
+    let mut detached = parser.detach_strings();
+    detached.internalize("z", ()).unwrap();
+    assert_eq!(detached.interner().resolve(symbol).as_deref(), Some("x"));
+    assert_eq!(parser.interner().symbol("z"), None);
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn parse_on_threads() {
+    fn assert_send_sync<Type: Send + Sync>(_: &Type) {}
+    
+    let keywords = ["(", ")"].into_iter().map(|keyword| (keyword.into(), ())).collect::<crate::keywords::KeywordTable<_>>();
+    let source = "(".repeat(50) + &")".repeat(50);
+    let mut parser = Parser::<()>::with_keywords(source, keywords.clone()).with_limits(Limits { depth: Some(60), steps: None });
+    let symbol = parser.internalize("x", ()).unwrap();
+    assert_send_sync(&parser);
+    
+    let nodes = std::thread::scope(|scope| {
+        let workers = (0..4)
+            .map(|_| {
+                let mut worker = parser.clone();
+                scope.spawn(move || {
+                    let mut nodes = Vec::new();
+                    for _ in 0..100 {
+                        let checkpoint = worker.checkpoint();
+                        nodes.push(worker.parse::<Nested>(&mut ()).unwrap());
+                        let _ = worker.internalize("y", ());
+                        worker.rewind(checkpoint);
+                        assert_eq!(worker.interner().resolve(symbol).as_deref(), Some("x"));
+                    }
+                    nodes
+                })
+            })
+            .collect::<Vec<_>>();
+        
+        // Parsers created from the same table parse alongside the clones.
+        let mut other = Parser::<()>::with_keywords("(())", keywords.clone());
+        assert!(other.parse::<Nested>(&mut ()).is_ok());
+        
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect::<Vec<_>>()
+    });
+    
+    assert_send_sync(&nodes);
+    assert_eq!(nodes.len(), 400);
+    assert_eq!(parser.steps(), 0);
+    assert_eq!(parser.interner().symbol("y"), None);
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn share_strings_between_threads() {
+    let parser = Parser::<()>::new("");
+    let mut clone = parser.clone();
+    
+    let (symbol, resolved) = std::thread::scope(|scope| {
+        let symbol = scope.spawn(move || clone.internalize("x", ()).unwrap()).join().unwrap();
+        let parser = &parser;
+        let resolved = scope.spawn(move || parser.interner().resolve(symbol)).join().unwrap();
+        (symbol, resolved)
+    });
+    
+    assert_eq!(resolved.as_deref(), Some("x"));
+    assert_eq!(parser.interner().symbol("x"), Some(symbol));
+}